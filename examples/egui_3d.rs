@@ -0,0 +1,131 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{panel::PanelQuad, setup, ScreenDescriptor};
+use euclid::{Angle, Size2D, Transform3D, Vector3D};
+use parrot::{painter::PassOp, transform::*, Painter, Rgba};
+use pigeon_2d::pigeon::OPENGL_TO_WGPU_MATRIX;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+// Size of the UI texture in pixels
+const PANEL_SIZE: u32 = 512;
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui in 3D")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(
+        Size2D::new(winsize.width, winsize.height),
+        wgpu::PresentMode::Fifo,
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+    );
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+
+    // The UI is rendered into this texture, which the quad then draws into the scene
+    let panel = paint.texture(
+        Size2D::new(PANEL_SIZE, PANEL_SIZE),
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        Some("Egui panel"),
+        false,
+    );
+    let mut quad = PanelQuad::new(&paint, wgpu::TextureFormat::Bgra8UnormSrgb);
+    quad.set_texture(&paint, &panel);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(
+                        Size2D::new(size.width, size.height),
+                        wgpu::PresentMode::Fifo,
+                        wgpu::TextureFormat::Bgra8UnormSrgb,
+                    );
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+
+                // Run egui as if it had a PANEL_SIZE window of its own
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(egui::Rect::from_min_size(
+                        egui::Pos2::ZERO,
+                        egui::vec2(PANEL_SIZE as f32, PANEL_SIZE as f32),
+                    )),
+                    pixels_per_point: Some(1.0),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("Egui in 3D");
+                        ui.label(format!("Running for {:.1}s", time));
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                        // Input isn't forwarded, see the egui_pigeon::panel docs
+                        ui.label("Mapping the cursor onto the panel is left to the app");
+                    });
+                });
+                let sd = ScreenDescriptor::new([PANEL_SIZE, PANEL_SIZE], 1.0);
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+                egui.render_layer(&mut paint, &panel);
+
+                // Center the panel on the origin, swing it around the Y axis and push it away from the camera
+                let model: Transform3D<f32, ObjectSpace, WorldSpace> = Transform3D::translation(-0.5, -0.5, 0.0)
+                    .then_rotate(0.0, 1.0, 0.0, Angle::radians(time.sin() * 0.6))
+                    .then_translate(Vector3D::new(0.0, 0.0, -2.0));
+                let aspect = winsize.width as f32 / winsize.height.max(1) as f32;
+                let mvp = model
+                    .then(&perspective(Angle::degrees(45.0), aspect, 0.1, 10.0))
+                    .then(&OPENGL_TO_WGPU_MATRIX.with_source::<ScreenSpace>().with_destination::<ScreenSpace>());
+                quad.set_transform(&paint, mvp);
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    quad.render(&mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// OpenGL style perspective projection
+fn perspective(fov_y: Angle<f32>, aspect: f32, near: f32, far: f32) -> Transform3D<f32, WorldSpace, ScreenSpace> {
+    let f = 1.0 / (fov_y.radians / 2.0).tan();
+    Transform3D::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, (far + near) / (near - far), -1.0,
+        0.0, 0.0, 2.0 * far * near / (near - far), 0.0,
+    )
+}
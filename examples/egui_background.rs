@@ -0,0 +1,87 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{painter::PassOp, Painter};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// A dark UI filling the window, on a backdrop drawn by the pipe instead of a clear
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui background")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    ctx.set_visuals(egui::Visuals::dark());
+    let mut egui = setup(&paint);
+    egui.set_background(Some(egui::Color32::from_rgb(18, 18, 24)));
+
+    let start = Instant::now();
+    let mut volume = 0.5;
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], window.scale_factor() as f32);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(sd.pixels_per_point),
+                    time: Some(start.elapsed().as_secs_f64()),
+                    ..Default::default()
+                };
+                // Only windows, no central panel, so the backdrop shows between them
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::Window::new("Settings").show(ctx, |ui| {
+                        ui.add(egui::Slider::new(&mut volume, 0.0..=1.0).text("volume"));
+                    });
+                    egui::Window::new("About").show(ctx, |ui| {
+                        ui.label("The window is never cleared, the pipe fills it before the UI");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Load(), &current_surface, None);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
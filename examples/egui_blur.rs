@@ -0,0 +1,236 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{blur, setup, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{Painter, Texture};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// Scrolling checkerboard with enough detail for the blur to show
+const SCENE_SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@group(0) @binding(0) var<uniform> r_time: vec4<f32>;
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let cell = floor((pos.xy + vec2<f32>(r_time.x * 40.0, 0.0)) / 24.0);
+    let check = abs(cell.x + cell.y) % 2.0;
+    return vec4<f32>(0.9 * check, 0.4, 0.9 - 0.8 * check, 1.0);
+}
+";
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui backdrop blur")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+    let mut blur = blur::BlurPass::new(&paint, FORMAT);
+
+    let scene = ScenePass::new(&paint);
+    let mut scene_target = scene_texture(&paint, winsize.width, winsize.height);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                    scene_target = scene_texture(&paint, size.width, size.height);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    let frosted = egui::Frame::window(&ctx.style()).fill(egui::Color32::from_black_alpha(96));
+                    let window = egui::Window::new("Frosted glass").frame(frosted).show(ctx, |ui| {
+                        ui.label("The scene behind this window is blurred");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                    });
+                    // The backdrop goes in the background layer so it is under the window's fill
+                    if let Some(window) = window {
+                        let shape = blur::backdrop_shape(
+                            window.response.rect,
+                            ctx.style().visuals.window_rounding,
+                            ctx.input().screen_rect().size(),
+                        );
+                        ctx.layer_painter(egui::LayerId::background()).add(shape);
+                    }
+                    egui::Window::new("Plain").show(ctx, |ui| {
+                        ui.label("This one is drawn as usual for comparison");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+                scene.set_time(&paint, time);
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                // Draw the scene offscreen for the blur to read, and blur it
+                scene.render(&mut frame.encoder, &scene_target.view);
+                blur.blur(&paint, &mut frame.encoder, &scene_target);
+                egui.set_blur_backdrop(&paint, &blur);
+                // Draw the scene again onto the surface with the UI on top
+                scene.render(&mut frame.encoder, &current_surface.view);
+                {
+                    let mut pass = frame.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &current_surface.view,
+                            resolve_target: None,
+                            ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
+                        })],
+                        depth_stencil_attachment: None,
+                    });
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// Pipeline drawing the checkerboard
+struct ScenePass {
+    pipeline: wgpu::RenderPipeline,
+    time: wgpu::Buffer,
+    binding: wgpu::BindGroup,
+}
+
+impl ScenePass {
+    fn new(paint: &Painter) -> Self {
+        let device = &paint.device.wgpu;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Scene shader"),
+            source: wgpu::ShaderSource::Wgsl(SCENE_SHADER.into()),
+        });
+        let time = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scene time"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Scene layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Scene binding"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: time.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Scene"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Scene"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        Self { pipeline, time, binding }
+    }
+
+    fn set_time(&self, paint: &Painter, time: f32) {
+        let time = [time, 0.0, 0.0, 0.0];
+        paint.device.queue.write_buffer(&self.time, 0, bytemuck::cast_slice(&time));
+    }
+
+    fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: true },
+            })],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.binding, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+fn scene_texture(paint: &Painter, width: u32, height: u32) -> Texture {
+    paint.texture(
+        Size2D::new(width.max(1), height.max(1)),
+        FORMAT,
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        Some("Scene"),
+        false,
+    )
+}
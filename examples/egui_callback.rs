@@ -0,0 +1,206 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{paint_callback, setup, CallbackFn, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// A triangle spinning about the middle of the viewport
+const TRIANGLE_SHADER: &str = "
+struct VertexOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> r_angle: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let angle = r_angle.x + f32(index) * 2.094;
+    var out: VertexOutput;
+    out.position = vec4<f32>(cos(angle) * 0.8, sin(angle) * 0.8, 0.0, 1.0);
+    out.color = vec4<f32>(f32(index == 0u), f32(index == 1u), f32(index == 2u), 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+";
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui paint callback")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+    // The callbacks find their pipeline here
+    egui.callback_resources().insert(Triangle::new(&paint));
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::Window::new("Custom wgpu drawing").show(ctx, |ui| {
+                        ui.label("The triangle is drawn by a paint callback");
+                        let (rect, _) = ui.allocate_exact_size(egui::vec2(200.0, 200.0), egui::Sense::hover());
+                        let callback = CallbackFn::new(
+                            move |paint, _, resources| {
+                                if let Some(triangle) = resources.get::<Triangle>() {
+                                    triangle.set_angle(paint, time);
+                                }
+                            },
+                            |_, pass, resources| {
+                                if let Some(triangle) = resources.get::<Triangle>() {
+                                    triangle.render(pass);
+                                }
+                            },
+                        );
+                        ui.painter().add(paint_callback(rect, callback));
+                        ui.label("and clipped to the window like any other widget");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// Pipeline and uniform for the triangle, kept in the callback resources
+struct Triangle {
+    pipeline: wgpu::RenderPipeline,
+    angle: wgpu::Buffer,
+    binding: wgpu::BindGroup,
+}
+
+impl Triangle {
+    fn new(paint: &Painter) -> Self {
+        let device = &paint.device.wgpu;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Triangle shader"),
+            source: wgpu::ShaderSource::Wgsl(TRIANGLE_SHADER.into()),
+        });
+        let angle = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Triangle angle"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Triangle layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Triangle binding"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: angle.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Triangle"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Triangle"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        Self { pipeline, angle, binding }
+    }
+
+    fn set_angle(&self, paint: &Painter, angle: f32) {
+        let angle = [angle, 0.0, 0.0, 0.0];
+        paint.device.queue.write_buffer(&self.angle, 0, bytemuck::cast_slice(&angle));
+    }
+
+    fn render<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.binding, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
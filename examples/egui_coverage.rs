@@ -0,0 +1,253 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{EguiPipeBuilder, ScreenDescriptor, COVERAGE_FORMAT};
+use euclid::Size2D;
+use parrot::{Painter, Texture};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// Draws stripes over the scene everywhere the UI didn't cover
+const POST_SHADER: &str = "
+struct VertexOutput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+@group(0) @binding(0) var r_scene: texture_2d<f32>;
+@group(0) @binding(1) var r_coverage: texture_2d<f32>;
+@group(0) @binding(2) var r_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = textureSample(r_scene, r_sampler, in.uv);
+    let coverage = textureSample(r_coverage, r_sampler, in.uv).r;
+    let stripe = step(0.5, fract((in.position.x + in.position.y) / 32.0));
+    let graded = vec4<f32>(scene.rgb * (0.4 + 0.6 * stripe), scene.a);
+    return mix(graded, scene, coverage);
+}
+";
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui coverage mask")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = EguiPipeBuilder::new().coverage_mask(true).build(&paint);
+
+    let post = PostPass::new(&paint);
+    let mut targets = Targets::new(&paint, &post, winsize.width, winsize.height);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                    targets = Targets::new(&paint, &post, size.width, size.height);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::Window::new("Untouched by post-processing").show(ctx, |ui| {
+                        ui.label("The stripes are only applied where the UI didn't draw");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                // Draw the scene, here just a clear color, and the UI with its coverage
+                {
+                    let ops = |load| wgpu::Operations { load, store: true };
+                    let mut pass = frame.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &[
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: &targets.scene.view,
+                                resolve_target: None,
+                                ops: ops(wgpu::LoadOp::Clear(wgpu::Color { r: 0.2, g: 0.5, b: 0.3, a: 1.0 })),
+                            }),
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: &targets.coverage.view,
+                                resolve_target: None,
+                                ops: ops(wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)),
+                            }),
+                        ],
+                        depth_stencil_attachment: None,
+                    });
+                    egui.render(&mut paint, &mut pass);
+                }
+                // Post-process onto the surface
+                {
+                    let mut pass = frame.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &current_surface.view,
+                            resolve_target: None,
+                            ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
+                        })],
+                        depth_stencil_attachment: None,
+                    });
+                    pass.set_pipeline(&post.pipeline);
+                    pass.set_bind_group(0, &targets.binding, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// Pipeline applying the post-processing
+struct PostPass {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+impl PostPass {
+    fn new(paint: &Painter) -> Self {
+        let device = &paint.device.wgpu;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post shader"),
+            source: wgpu::ShaderSource::Wgsl(POST_SHADER.into()),
+        });
+        let texture = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post layout"),
+            entries: &[
+                texture(0),
+                texture(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+        Self { pipeline, layout, sampler }
+    }
+}
+
+// Offscreen scene and coverage targets read by the post-processing
+struct Targets {
+    scene: Texture,
+    coverage: Texture,
+    binding: wgpu::BindGroup,
+}
+
+impl Targets {
+    fn new(paint: &Painter, post: &PostPass, width: u32, height: u32) -> Self {
+        let size = Size2D::new(width.max(1), height.max(1));
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        let scene = paint.texture(size, FORMAT, usage, Some("Scene"), false);
+        let coverage = paint.texture(size, COVERAGE_FORMAT, usage, Some("UI coverage"), false);
+        let binding = paint.device.wgpu.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post binding"),
+            layout: &post.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&scene.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&coverage.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&post.sampler),
+                },
+            ],
+        });
+        Self { scene, coverage, binding }
+    }
+}
@@ -1,6 +1,6 @@
 extern crate wgpu;
 extern crate winit;
-use egui_pigeon::{setup, EguiPipe, ScreenDescriptor};
+use egui_pigeon::{cursor::CursorSheet, setup, ClipRounding, ClipSpace, DebugMode, EguiPipe, ScreenDescriptor};
 use euclid::Size2D;
 use euclid::Transform3D;
 use parrot::{painter::PassOp, transform::*, Painter};
@@ -43,16 +43,40 @@ fn main() {
         1,
     );
 
-    let mut state = egui_winit::State::new(
-        wgpu::Limits::default()
-            .max_texture_dimension_2d
-            .try_into()
-            .unwrap(),
-        &window,
-    );
+    let mut state = egui_winit::State::new(egui_pigeon::max_texture_side(&p.paint) as usize, &window);
 
     let ctx = egui::Context::default();
     let mut demo_windows = egui_demo_lib::DemoWindows::default();
+    let mut debug_mode = DebugMode::Off;
+    let mut stretch = false;
+    let mut hidden = false;
+    let mut bundles = false;
+    let mut pulse = false;
+    let mut software_cursor = false;
+    let mut ui_scale = 1.0;
+    let mut clip_rounding = ClipRounding::default();
+    let mut pixel_snapping = false;
+    // When the fade out and back in started
+    let mut fade: Option<Instant> = None;
+    // CPU time spent encoding egui's draws last frame, shown once a second so the label doesn't
+    // change the UI (and re-record the bundles) every frame
+    let mut encode_time = std::time::Duration::ZERO;
+    let mut shown_encode_time = (encode_time, Instant::now());
+
+    // Checkerboard drawn in place of images that haven't loaded yet
+    let placeholder = ctx.load_texture(
+        "loading",
+        egui::ColorImage::from_rgba_unmultiplied(
+            [16, 16],
+            &(0..16 * 16)
+                .flat_map(|i| if (i % 16 / 4 + i / 64) % 2 == 0 { [200; 4] } else { [120, 120, 120, 255] })
+                .collect::<Vec<u8>>(),
+        ),
+    );
+    p.egui.set_fallback_texture(Some(placeholder.id()));
+    // An image that takes a few seconds to "download"
+    let start = Instant::now();
+    let mut image: Option<egui::TextureHandle> = None;
 
     let event_loop_proxy = egui::mutex::Mutex::new(event_loop.create_proxy());
     ctx.set_request_repaint_callback(move || {
@@ -88,14 +112,171 @@ fn main() {
                 window.request_redraw();
             }
             Event::RedrawRequested(_) => {
-                let raw_input = state.take_egui_input(&window);
+                let mut raw_input = state.take_egui_input(&window);
+                // The pipe applies the UI scale itself, egui gets the combined scale to lay out with
+                let ppp = window.scale_factor() as f32;
+                p.egui.set_ui_scale(ui_scale);
+                let scaled = ScreenDescriptor::new([p.screen.width as u32, p.screen.height as u32], ppp).with_ui_scale(ui_scale);
+                raw_input.pixels_per_point = Some(scaled.pixels_per_point);
+                if raw_input.screen_rect.is_some() {
+                    raw_input.screen_rect = Some(scaled.screen_rect());
+                }
+                // Lay the UI out at half width and stretch it back out 2:1 when rendering
+                if stretch {
+                    if let Some(rect) = &mut raw_input.screen_rect {
+                        rect.max.x /= 2.0;
+                    }
+                    for event in &mut raw_input.events {
+                        match event {
+                            egui::Event::PointerMoved(pos) | egui::Event::PointerButton { pos, .. } => pos.x /= 2.0,
+                            _ => (),
+                        }
+                    }
+                }
                 let sd = ScreenDescriptor {
                     size_in_pixels: [p.screen.width as u32, p.screen.height as u32],
-                    pixels_per_point: state.pixels_per_point(),
+                    pixels_per_point: ppp,
+                    render_scale: 1.0,
+                    scale_factors: stretch.then_some([ppp * 2.0, ppp]),
+                    clip_space: ClipSpace::Logical,
                 };
+                let stats = p.egui.last_render_stats();
+                let upload_bytes = p.egui.last_vertex_upload_bytes();
+                let buffer_bytes = p.egui.buffer_memory_bytes();
+                let draws: Vec<_> = p.egui.draw_list().collect();
+                let [sx, sy] = sd.with_ui_scale(ui_scale).scale_factors();
+                if shown_encode_time.1.elapsed().as_secs() >= 1 {
+                    shown_encode_time = (encode_time, Instant::now());
+                }
                 let full_output = ctx.run(raw_input, |ctx| {
-                    demo_windows.ui(&ctx);
+                    // Escape hides the whole UI, which should record no egui commands
+                    if ctx.input().key_pressed(egui::Key::Escape) {
+                        hidden = !hidden;
+                    }
+                    if hidden {
+                        return;
+                    }
+                    demo_windows.ui(ctx);
+                    // Last frame's draws, outlining the one under the cursor
+                    egui::Window::new("Draw list").show(ctx, |ui| {
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            for (i, draw) in draws.iter().enumerate() {
+                                let r = draw.pixel_rect;
+                                let response = ui.label(format!(
+                                    "{}: {} indices, {:?}, {}x{} at {},{}{}",
+                                    i,
+                                    draw.index_count,
+                                    draw.tex_id,
+                                    r.size.width,
+                                    r.size.height,
+                                    r.origin.x,
+                                    r.origin.y,
+                                    if draw.opaque { ", opaque" } else { "" }
+                                ));
+                                if response.hovered() {
+                                    let rect = egui::Rect::from_min_size(
+                                        egui::pos2(r.origin.x as f32 / sx, r.origin.y as f32 / sy),
+                                        egui::vec2(r.size.width as f32 / sx, r.size.height as f32 / sy),
+                                    );
+                                    ctx.debug_painter().rect_stroke(rect, 0.0, (2.0, egui::Color32::YELLOW));
+                                }
+                            }
+                        });
+                    });
+                    egui::Window::new("Egui pigeon").show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut debug_mode, DebugMode::Off, "Off");
+                            ui.radio_value(&mut debug_mode, DebugMode::Overdraw, "Overdraw");
+                            ui.radio_value(&mut debug_mode, DebugMode::ClipRects, "Clip rects");
+                            ui.radio_value(&mut debug_mode, DebugMode::TextureTint, "Texture tint");
+                        });
+                        ui.checkbox(&mut stretch, "Stretch 2:1");
+                        ui.add(egui::Slider::new(&mut ui_scale, 0.5..=2.0).text("UI scale"));
+                        if ui.button("Fade").clicked() {
+                            fade = Some(Instant::now());
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Clip rounding");
+                            ui.radio_value(&mut clip_rounding, ClipRounding::Round, "Round");
+                            ui.radio_value(&mut clip_rounding, ClipRounding::Floor, "Floor");
+                            ui.radio_value(&mut clip_rounding, ClipRounding::Expand, "Expand");
+                        });
+                        // Two panels clipped edge to edge between pixels, a seam shows the window behind
+                        let (rect, _) = ui.allocate_exact_size(egui::vec2(200.0, 16.0), egui::Sense::hover());
+                        let split = rect.min.x + 100.3;
+                        let left = egui::Rect::from_x_y_ranges(rect.min.x..=split, rect.y_range());
+                        let right = egui::Rect::from_x_y_ranges(split..=rect.max.x, rect.y_range());
+                        for (half, color) in [(left, egui::Color32::DARK_RED), (right, egui::Color32::DARK_BLUE)] {
+                            ui.painter_at(half).rect_filled(rect, 0.0, color);
+                        }
+                        ui.checkbox(&mut bundles, "Render bundles");
+                        ui.checkbox(&mut software_cursor, "Software cursor");
+                        ui.checkbox(&mut pixel_snapping, "Pixel snapping");
+                        // Only the button's colors change, so only they are uploaded
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut pulse, "Pulse");
+                            let t = if pulse { (start.elapsed().as_secs_f32() * 3.0).sin() * 0.5 + 0.5 } else { 0.0 };
+                            ui.add(egui::Button::new("Pulsing").fill(egui::Color32::from_rgb(60, (60.0 + 120.0 * t) as u8, 60)));
+                            if pulse {
+                                ctx.request_repaint();
+                            }
+                        });
+                        ui.label(format!("Uploaded {} vertex bytes", upload_bytes));
+                        ui.label(format!("Buffers hold {} vertex and {} index bytes", buffer_bytes.0, buffer_bytes.1));
+                        ui.label(format!("Encoded in {:?}", shown_encode_time.0));
+                        ui.label(format!(
+                            "{} draws, {} scissors, {} bindings, {} skipped",
+                            stats.draw_calls, stats.scissor_changes, stats.binding_changes, stats.skipped_groups
+                        ));
+                        // Nested scroll areas to check the clip rect outlines against
+                        egui::ScrollArea::vertical().id_source("outer").max_height(120.0).show(ui, |ui| {
+                            for i in 0..4 {
+                                ui.label(format!("Outer row {}", i));
+                                egui::ScrollArea::horizontal().id_source(i).show(ui, |ui| {
+                                    ui.label("A long inner row that scrolls sideways inside the outer scroll area");
+                                });
+                            }
+                        });
+                        if image.is_none() && start.elapsed().as_secs() >= 3 {
+                            let pixels = (0..64 * 64)
+                                .flat_map(|i| [(i % 64 * 4) as u8, (i / 64 * 4) as u8, 160, 255])
+                                .collect::<Vec<u8>>();
+                            image = Some(ctx.load_texture("image", egui::ColorImage::from_rgba_unmultiplied([64, 64], &pixels)));
+                        }
+                        // Until the image loads, the unregistered id is drawn with the placeholder
+                        let id = image.as_ref().map_or(egui::TextureId::User(0), |i| i.id());
+                        ui.image(id, [64.0, 64.0]);
+                        if image.is_none() {
+                            ctx.request_repaint();
+                        }
+                    });
                 });
+                p.egui.set_debug_mode(debug_mode);
+                p.egui.set_clip_rounding(clip_rounding);
+                if pixel_snapping != p.egui.pixel_snapping() {
+                    p.egui.set_pixel_snapping(pixel_snapping);
+                }
+                // Fade the whole UI down to 20% and back over two seconds
+                let elapsed = fade.map(|f| f.elapsed().as_secs_f32()).filter(|e| *e < 2.0);
+                if elapsed.is_none() {
+                    fade = None;
+                }
+                let alpha = elapsed.map_or(1.0, |e| 1.0 - 0.8 * (e * std::f32::consts::FRAC_PI_2).sin());
+                if alpha != p.egui.tint().a() {
+                    p.egui.set_tint(egui::Rgba::from_white_alpha(alpha));
+                }
+                if fade.is_some() {
+                    ctx.request_repaint();
+                }
+                if bundles != p.egui.render_bundles() {
+                    p.egui.set_render_bundles(bundles);
+                }
+                // Hide the hardware cursor while egui draws its own
+                if software_cursor != p.egui.software_cursor().is_some() {
+                    p.egui.set_software_cursor(software_cursor.then(CursorSheet::default));
+                    window.set_cursor_visible(!software_cursor);
+                }
+                p.egui.set_cursor(ctx.input().pointer.hover_pos(), full_output.platform_output.cursor_icon);
                 state.handle_platform_output(&window, &ctx, full_output.platform_output);
                 let clipped_primatives = ctx.tessellate(full_output.shapes);
                 p.paint.update_pipeline(&mut p.egui, (full_output.textures_delta, clipped_primatives, sd));
@@ -103,9 +284,11 @@ fn main() {
                     &mut p,
                     false,
                     |_| {},
-                    |p, _c, mut pass, _ob| {
+                    |p, _c, pass, _ob| {
                         pass.push_debug_group("Egui rpass");
-                        p.egui.render(&mut p.paint, &mut pass);
+                        let start = Instant::now();
+                        p.egui.render(&mut p.paint, pass);
+                        encode_time = start.elapsed();
                         pass.pop_debug_group();
                     }
                 );
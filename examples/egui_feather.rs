@@ -0,0 +1,89 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// Shapes with a slider for how soft their antialiased edges are
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui feathering")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+
+    let start = Instant::now();
+    let mut feather = 1.0;
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], window.scale_factor() as f32);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(sd.pixels_per_point),
+                    time: Some(start.elapsed().as_secs_f64()),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.add(egui::Slider::new(&mut feather, 0.0..=2.0).text("feather"));
+                        ui.label("Below 1 sharpens the edges of shapes, above 1 softens them");
+                        let (rect, _) = ui.allocate_exact_size(egui::vec2(240.0, 120.0), egui::Sense::hover());
+                        let painter = ui.painter_at(rect);
+                        painter.circle_filled(rect.left_center() + egui::vec2(60.0, 0.0), 50.0, egui::Color32::LIGHT_BLUE);
+                        painter.rect_stroke(rect.shrink(4.0), 12.0, (1.5, egui::Color32::WHITE));
+                        painter.line_segment([rect.left_top(), rect.right_bottom()], (1.0, egui::Color32::YELLOW));
+                        let _ = ui.button("A rounded button");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                egui.set_feather(feather);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.1, 0.1, 1.0)), &current_surface, None);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
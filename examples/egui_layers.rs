@@ -0,0 +1,230 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// A triangle spinning about the middle of the window
+const TRIANGLE_SHADER: &str = "
+struct VertexOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> r_angle: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let angle = r_angle.x + f32(index) * 2.094;
+    var out: VertexOutput;
+    out.position = vec4<f32>(cos(angle) * 0.6, sin(angle) * 0.6, 0.0, 1.0);
+    out.color = vec4<f32>(f32(index == 0u), f32(index == 1u), f32(index == 2u), 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+";
+
+// The panels are drawn, then the triangle over them, then the windows and tooltips over that
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui layers")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+    let triangle = Triangle::new(&paint);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+                        ui.label("Panels are below the triangle");
+                    });
+                    egui::SidePanel::left("tools").show(ctx, |ui| {
+                        for i in 0..10 {
+                            ui.label(format!("Tool {}", i));
+                        }
+                    });
+                    // Panels are in the background order and windows after it, so a marker painted
+                    // between the two shows where the UI is split
+                    let split = egui::LayerId::new(egui::Order::PanelResizeLine, egui::Id::new("split"));
+                    let mut marker = ctx.layer_painter(split);
+                    marker.set_clip_rect(egui::Rect::NOTHING);
+                    marker.add(egui::Shape::Noop);
+
+                    egui::Window::new("Above the triangle").show(ctx, |ui| {
+                        ui.label("Windows and tooltips are drawn over it");
+                    });
+                    let pos = ctx.input().screen_rect().center();
+                    egui::show_tooltip_at(ctx, egui::Id::new("triangle tooltip"), Some(pos), |ui| {
+                        ui.label("A tooltip over the triangle");
+                    });
+                });
+
+                // Tessellate each side of the marker on its own, so no mesh spans both
+                let mut shapes = full_output.shapes;
+                let split = shapes
+                    .iter()
+                    .position(|s| s.0 == egui::Rect::NOTHING && matches!(s.1, egui::Shape::Noop))
+                    .unwrap_or(shapes.len());
+                let foreground = shapes.split_off(split);
+                let mut clipped_primatives = ctx.tessellate(shapes);
+                let below = clipped_primatives.len();
+                clipped_primatives.extend(ctx.tessellate(foreground));
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+                triangle.set_angle(&paint, time);
+
+                // The first group cut from a foreground primitive
+                let split = egui
+                    .groups
+                    .iter()
+                    .position(|g| g.primitive() >= Some(below))
+                    .unwrap_or_else(|| egui.group_count());
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    egui.render_range(&paint, &mut pass, 0..split);
+                    pass.set_scissor_rect(0, 0, winsize.width, winsize.height);
+                    triangle.render(&mut pass);
+                    egui.render_range(&paint, &mut pass, split..egui.group_count());
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// Pipeline and uniform for the triangle
+struct Triangle {
+    pipeline: wgpu::RenderPipeline,
+    angle: wgpu::Buffer,
+    binding: wgpu::BindGroup,
+}
+
+impl Triangle {
+    fn new(paint: &Painter) -> Self {
+        let device = &paint.device.wgpu;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Triangle shader"),
+            source: wgpu::ShaderSource::Wgsl(TRIANGLE_SHADER.into()),
+        });
+        let angle = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Triangle angle"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Triangle layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Triangle binding"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: angle.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Triangle"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Triangle"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        Self { pipeline, angle, binding }
+    }
+
+    fn set_angle(&self, paint: &Painter, angle: f32) {
+        let angle = [angle, 0.0, 0.0, 0.0];
+        paint.device.queue.write_buffer(&self.angle, 0, bytemuck::cast_slice(&angle));
+    }
+
+    fn render<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.binding, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,124 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor};
+use euclid::{Point2D, Rect, Size2D};
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{ElementState, Event, MouseButton, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+// Aspect ratio the game is drawn at, whatever the window's
+const ASPECT: f32 = 16.0 / 9.0;
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui letterboxed")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+
+    let start = Instant::now();
+    let mut events = vec![];
+    let mut clicks = 0;
+
+    event_loop.run(move |event, _, control_flow| {
+        // The largest 16:9 rect centred in the window, with bars on the other two sides
+        let game = game_rect(winsize.width, winsize.height);
+        let ppp = window.scale_factor() as f32;
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                // egui only sees the game's rect, so pointer positions are made relative to it
+                WindowEvent::CursorMoved { position, .. } => {
+                    let pos = egui::pos2(
+                        (position.x as f32 - game.origin.x as f32) / ppp,
+                        (position.y as f32 - game.origin.y as f32) / ppp,
+                    );
+                    events.push(egui::Event::PointerMoved(pos));
+                }
+                WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                    if let Some(pos) = ctx.input().pointer.hover_pos() {
+                        events.push(egui::Event::PointerButton {
+                            pos,
+                            button: egui::PointerButton::Primary,
+                            pressed: state == ElementState::Pressed,
+                            modifiers: Default::default(),
+                        });
+                    }
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let sd = ScreenDescriptor::new([game.width(), game.height()], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(start.elapsed().as_secs_f64()),
+                    events: std::mem::take(&mut events),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("Letterboxed UI");
+                        ui.label("The panel fills the game's 16:9 area and stops at the bars");
+                        if ui.button(format!("Clicked {} times", clicks)).clicked() {
+                            clicks += 1;
+                        }
+                    });
+                    egui::Window::new("Drag me").show(ctx, |ui| {
+                        ui.label("Dragged past the edge, the window is cut off at the bars");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                egui.set_destination(Some(game));
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.0, 0.0, 0.0, 1.0)), &current_surface, None);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+fn game_rect(width: u32, height: u32) -> Rect<u32, parrot::transform::ScreenSpace> {
+    let (w, h) = (width as f32, height as f32);
+    let size = if w / h.max(1.0) > ASPECT {
+        Size2D::new(h * ASPECT, h)
+    } else {
+        Size2D::new(w, w / ASPECT)
+    };
+    let origin = Point2D::new((w - size.width) / 2.0, (h - size.height) / 2.0);
+    Rect::new(origin, size).round_in().to_u32()
+}
@@ -0,0 +1,139 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{panel::PanelQuad, setup, ScreenDescriptor, TextureStore};
+use euclid::{Angle, Size2D, Transform3D, Vector3D};
+use parrot::{painter::PassOp, transform::*, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+// Size of the mirrored UI texture in pixels
+const MIRROR_SIZE: u32 = 512;
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui shared textures")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(
+        Size2D::new(winsize.width, winsize.height),
+        wgpu::PresentMode::Fifo,
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+    );
+
+    let ctx = egui::Context::default();
+    // Both pipes draw the same context, so they can share its font atlas
+    let store = TextureStore::shared();
+    let mut egui = setup(&paint);
+    egui.set_texture_store(Some(store.clone()));
+    let mut mirror = setup(&paint);
+    mirror.set_texture_store(Some(store));
+
+    // The mirror pipe renders into this texture, which the quad draws tilted in the corner
+    let panel = paint.texture(
+        Size2D::new(MIRROR_SIZE, MIRROR_SIZE),
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        Some("Egui mirror"),
+        false,
+    );
+    let mut quad = PanelQuad::new(&paint, wgpu::TextureFormat::Bgra8UnormSrgb);
+    quad.set_texture(&paint, &panel);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(
+                        Size2D::new(size.width, size.height),
+                        wgpu::PresentMode::Fifo,
+                        wgpu::TextureFormat::Bgra8UnormSrgb,
+                    );
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::Window::new("Shared textures").show(ctx, |ui| {
+                        ui.label("The mirror in the corner uses the same font atlas");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+
+                // Fit the whole screen into the mirror texture
+                let screen = sd.screen_rect().size();
+                let mirror_sd = ScreenDescriptor::new(
+                    [MIRROR_SIZE, MIRROR_SIZE],
+                    MIRROR_SIZE as f32 / screen.x.max(screen.y).max(1.0),
+                );
+                // The main pipe uploads the textures, and has to be prepared first
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives.clone(), sd));
+                paint.update_pipeline(&mut mirror, (Default::default(), clipped_primatives, mirror_sd));
+                mirror.render_layer(&mut paint, &panel);
+
+                let aspect = winsize.width as f32 / winsize.height.max(1) as f32;
+                let model: Transform3D<f32, ObjectSpace, WorldSpace> = Transform3D::translation(-0.5, -0.5, 0.0)
+                    .then_rotate(0.0, 1.0, 0.0, Angle::radians(0.5 + time.sin() * 0.2))
+                    .then_translate(Vector3D::new(1.2 * aspect, -0.8, -3.0));
+                let mvp = model
+                    .then(&perspective(Angle::degrees(45.0), aspect, 0.1, 10.0))
+                    .then(&pigeon_2d::pigeon::OPENGL_TO_WGPU_MATRIX.with_source::<ScreenSpace>().with_destination::<ScreenSpace>());
+                quad.set_transform(&paint, mvp);
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    quad.render(&mut pass);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// OpenGL style perspective projection
+fn perspective(fov_y: Angle<f32>, aspect: f32, near: f32, far: f32) -> Transform3D<f32, WorldSpace, ScreenSpace> {
+    let f = 1.0 / (fov_y.radians / 2.0).tan();
+    Transform3D::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, (far + near) / (near - far), -1.0,
+        0.0, 0.0, 2.0 * far * near / (near - far), 0.0,
+    )
+}
@@ -0,0 +1,286 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor, StencilMask};
+use euclid::Size2D;
+use parrot::Painter;
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+// Writes the stencil inside an ellipse filling the window
+const MASK_SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@group(0) @binding(0) var<uniform> r_size: vec4<f32>;
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let ndc = pos.xy / r_size.xy * 2.0 - 1.0;
+    if (length(ndc) > 0.9) {
+        discard;
+    }
+    return vec4<f32>(0.0);
+}
+";
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui stencil mask")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(
+        Size2D::new(winsize.width, winsize.height),
+        wgpu::PresentMode::Fifo,
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+    );
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+    // Only draw the UI where the mask pass wrote 1
+    egui.set_stencil_mask(Some(StencilMask {
+        format: STENCIL_FORMAT,
+        compare: wgpu::CompareFunction::Equal,
+        reference: 1,
+    }));
+
+    let mask = MaskPass::new(&paint);
+    let mut stencil = stencil_texture(&paint, winsize.width, winsize.height);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(
+                        Size2D::new(size.width, size.height),
+                        wgpu::PresentMode::Fifo,
+                        wgpu::TextureFormat::Bgra8UnormSrgb,
+                    );
+                    stencil = stencil_texture(&paint, size.width, size.height);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+                let ppp = window.scale_factor() as f32;
+
+                let sd = ScreenDescriptor::new([winsize.width, winsize.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("Egui behind a stencil mask");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                        for i in 0..40 {
+                            ui.label(format!("Only the part of row {} inside the ellipse is drawn", i));
+                        }
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+                mask.set_size(&paint, winsize.width, winsize.height);
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                // Write the mask
+                {
+                    let mut pass = stencil_pass(
+                        &mut frame.encoder,
+                        &current_surface.view,
+                        &stencil,
+                        wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 }),
+                        wgpu::LoadOp::Clear(0),
+                    );
+                    mask.render(&mut pass);
+                }
+                // Draw egui through it
+                {
+                    let mut pass = stencil_pass(
+                        &mut frame.encoder,
+                        &current_surface.view,
+                        &stencil,
+                        wgpu::LoadOp::Load,
+                        wgpu::LoadOp::Load,
+                    );
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// Pipeline writing 1 into the stencil inside the ellipse
+struct MaskPass {
+    pipeline: wgpu::RenderPipeline,
+    size: wgpu::Buffer,
+    binding: wgpu::BindGroup,
+}
+
+impl MaskPass {
+    fn new(paint: &Painter) -> Self {
+        let device = &paint.device.wgpu;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mask shader"),
+            source: wgpu::ShaderSource::Wgsl(MASK_SHADER.into()),
+        });
+        let size = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mask size"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Mask layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mask binding"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: size.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mask"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let face = wgpu::StencilFaceState {
+            compare: wgpu::CompareFunction::Always,
+            pass_op: wgpu::StencilOperation::Replace,
+            ..Default::default()
+        };
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mask"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: STENCIL_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState {
+                    front: face,
+                    back: face,
+                    read_mask: !0,
+                    write_mask: !0,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            // Only the stencil is written
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::empty(),
+                })],
+            }),
+            multiview: None,
+        });
+        Self { pipeline, size, binding }
+    }
+
+    fn set_size(&self, paint: &Painter, width: u32, height: u32) {
+        let size = [width as f32, height as f32, 0.0, 0.0];
+        paint.device.queue.write_buffer(&self.size, 0, bytemuck::cast_slice(&size));
+    }
+
+    fn render<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.binding, &[]);
+        pass.set_stencil_reference(1);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+fn stencil_texture(paint: &Painter, width: u32, height: u32) -> wgpu::TextureView {
+    paint
+        .device
+        .wgpu
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Stencil"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn stencil_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    color: &'a wgpu::TextureView,
+    stencil: &'a wgpu::TextureView,
+    color_load: wgpu::LoadOp<wgpu::Color>,
+    stencil_load: wgpu::LoadOp<u32>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: color,
+            resolve_target: None,
+            ops: wgpu::Operations { load: color_load, store: true },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: stencil,
+            depth_ops: None,
+            stencil_ops: Some(wgpu::Operations { load: stencil_load, store: true }),
+        }),
+    })
+}
@@ -0,0 +1,206 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{panel::PanelQuad, setup, ScreenDescriptor};
+use euclid::{Angle, Size2D, Transform3D, Vector3D};
+use parrot::{painter::PassOp, transform::*, Painter, Rgba};
+use pigeon_2d::pigeon::OPENGL_TO_WGPU_MATRIX;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::num::NonZeroU32;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+// Size of the UI texture in pixels
+const PANEL_SIZE: u32 = 512;
+// Size of each eye's view in pixels
+const EYE_SIZE: u32 = 512;
+// Distance between the eyes in world units
+const EYE_SEPARATION: f32 = 0.064;
+
+// Stands in for a headset: both eyes are drawn into a two layer texture in one multiview pass, then
+// shown side by side in the window. An XR runtime would hand over its swapchain's array texture
+// and per eye view projections instead.
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui in stereo")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    // Needs a device created with wgpu::Features::MULTIVIEW. Painter::for_surface doesn't request
+    // any optional features, so this exits until the painter's device can be given some.
+    let mut stereo = match PanelQuad::new_multiview(&paint, FORMAT, NonZeroU32::new(2).unwrap()) {
+        Ok(quad) => quad,
+        Err(e) => {
+            eprintln!("Can't draw in stereo: {}", e);
+            return;
+        }
+    };
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+
+    // The UI is rendered into this texture once, then drawn into both eyes
+    let panel = paint.texture(
+        Size2D::new(PANEL_SIZE, PANEL_SIZE),
+        FORMAT,
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        Some("Egui panel"),
+        false,
+    );
+    stereo.set_texture(&paint, &panel);
+
+    // One layer per eye
+    let eyes = paint.device.wgpu.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Eyes"),
+        size: wgpu::Extent3d {
+            width: EYE_SIZE,
+            height: EYE_SIZE,
+            depth_or_array_layers: 2,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    });
+    let eyes_view = eyes.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    });
+
+    // Each eye is copied out and drawn on one half of the window
+    let halves: Vec<_> = (0..2)
+        .map(|eye| {
+            let texture = paint.texture(
+                Size2D::new(EYE_SIZE, EYE_SIZE),
+                FORMAT,
+                wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                Some("Eye"),
+                false,
+            );
+            let mut quad = PanelQuad::new(&paint, FORMAT);
+            quad.set_texture(&paint, &texture);
+            let mvp = Transform3D::scale(1.0, 2.0, 1.0).then_translate(Vector3D::new(eye as f32 - 1.0, -1.0, 0.0));
+            quad.set_transform(&paint, mvp);
+            (texture, quad)
+        })
+        .collect();
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+
+                // Run egui as if it had a PANEL_SIZE window of its own
+                let sd = ScreenDescriptor::new([PANEL_SIZE, PANEL_SIZE], 1.0);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(1.0),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("Egui in stereo");
+                        ui.label("Both eyes are drawn in one multiview pass");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+                egui.render_layer(&mut paint, &panel);
+
+                // Float the panel in front of the viewer, seen from each eye
+                let model: Transform3D<f32, ObjectSpace, WorldSpace> = Transform3D::translation(-0.5, -0.5, 0.0)
+                    .then_rotate(0.0, 1.0, 0.0, Angle::radians(time.sin() * 0.4))
+                    .then_translate(Vector3D::new(0.0, 0.0, -1.5));
+                let projection = perspective(Angle::degrees(90.0), 1.0, 0.1, 10.0)
+                    .then(&OPENGL_TO_WGPU_MATRIX.with_source::<ScreenSpace>().with_destination::<ScreenSpace>());
+                let mvps: Vec<_> = [-0.5_f32, 0.5]
+                    .iter()
+                    .map(|side| {
+                        let view = Transform3D::translation(-side * EYE_SEPARATION, 0.0, 0.0);
+                        model.then(&view).then(&projection)
+                    })
+                    .collect();
+                stereo.set_view_transforms(&paint, &mvps);
+
+                let mut frame = paint.frame();
+                {
+                    let mut pass = frame.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Eyes"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &eyes_view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 }),
+                                store: true,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                    });
+                    stereo.render(&mut pass);
+                }
+                for (eye, (texture, _)) in halves.iter().enumerate() {
+                    frame.encoder.copy_texture_to_texture(
+                        wgpu::ImageCopyTexture {
+                            texture: &eyes,
+                            mip_level: 0,
+                            origin: wgpu::Origin3d { x: 0, y: 0, z: eye as u32 },
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        texture.wgpu.as_image_copy(),
+                        texture.extent,
+                    );
+                }
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.0, 0.0, 0.0, 1.0)), &current_surface, None);
+                    for (_, quad) in &halves {
+                        quad.render(&mut pass);
+                    }
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
+
+// OpenGL style perspective projection
+fn perspective(fov_y: Angle<f32>, aspect: f32, near: f32, far: f32) -> Transform3D<f32, WorldSpace, ScreenSpace> {
+    let f = 1.0 / (fov_y.radians / 2.0).tan();
+    Transform3D::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, (far + near) / (near - far), -1.0,
+        0.0, 0.0, 2.0 * far * near / (near - far), 0.0,
+    )
+}
@@ -0,0 +1,100 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor, SubpixelOrder, TextureStore};
+use euclid::{Point2D, Rect, Size2D};
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+// The same UI twice, grayscale antialiased on the left and subpixel antialiased on the right
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui subpixel text")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    // Both pipes draw the same context, so they share its font atlas
+    let store = TextureStore::shared();
+    let mut grayscale = setup(&paint);
+    grayscale.set_texture_store(Some(store.clone()));
+    let mut subpixel = setup(&paint);
+    subpixel.set_texture_store(Some(store));
+    subpixel.set_subpixel_text(Some(SubpixelOrder::Rgb));
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let ppp = window.scale_factor() as f32;
+                let half = Size2D::new(winsize.width / 2, winsize.height);
+
+                // Lay the UI out for one half of the window
+                let sd = ScreenDescriptor::new([half.width, half.height], ppp);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(ppp),
+                    time: Some(start.elapsed().as_secs_f64()),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("Subpixel text");
+                        ui.label("Grayscale on the left, RGB subpixels on the right.");
+                        ui.label("Look closely, or take a screenshot and zoom in, to see the colored edges.");
+                        ui.small("Small text gains the most from the extra horizontal resolution");
+                        ui.monospace("fn main() { println!(\"Hello\"); }");
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+
+                grayscale.set_destination(Some(Rect::new(Point2D::zero(), half)));
+                subpixel.set_destination(Some(Rect::new(Point2D::new(half.width, 0), half)));
+                // The grayscale pipe uploads the textures, and has to be prepared first
+                paint.update_pipeline(&mut grayscale, (full_output.textures_delta, clipped_primatives.clone(), sd));
+                paint.update_pipeline(&mut subpixel, (Default::default(), clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.0, 0.0, 0.0, 1.0)), &current_surface, None);
+                    grayscale.render(&mut paint, &mut pass);
+                    subpixel.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
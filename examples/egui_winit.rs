@@ -0,0 +1,79 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{EguiWinit, ScreenDescriptor};
+use euclid::Size2D;
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui in one call")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let mut egui = EguiWinit::new(&paint, &window);
+    let mut name = String::from("pigeon");
+    let mut age = 3;
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::WindowEvent { event: win_event, .. } => {
+                match win_event {
+                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                    // Minimized windows are resized to 0x0, which a surface can't be configured to
+                    WindowEvent::Resized(size) if size.width == 0 || size.height == 0 => (),
+                    WindowEvent::Resized(size) => {
+                        paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                    }
+                    _ => (),
+                }
+                egui.on_event(&win_event);
+                window.request_redraw();
+            }
+            Event::RedrawRequested(_) if ScreenDescriptor::from_window(&window).is_minimized() => (),
+            Event::RedrawRequested(_) => {
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                let repaint = {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    egui.run_frame(&window, &mut paint, &mut pass, |ctx| {
+                        egui::Window::new("Egui in one call").show(ctx, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Name");
+                                ui.text_edit_singleline(&mut name);
+                            });
+                            ui.add(egui::Slider::new(&mut age, 0..=20).text("age"));
+                            ui.label(format!("Hello {}, age {}", name, age));
+                        });
+                    })
+                };
+                paint.present(frame);
+                if repaint {
+                    window.request_redraw();
+                }
+            }
+            _ => (),
+        }
+    });
+}
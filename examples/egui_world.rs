@@ -0,0 +1,96 @@
+extern crate wgpu;
+extern crate winit;
+use egui_pigeon::{setup, ScreenDescriptor, WorldTransform};
+use euclid::{Angle, Size2D, Transform3D, Vector3D};
+use parrot::{painter::PassOp, Painter, Rgba};
+use pigeon_2d::pipeline::Render;
+use pigeon_parrot as parrot;
+use pollster::FutureExt;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+// Size of the UI in points
+const UI_SIZE: u32 = 320;
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    // Create an event loop
+    let event_loop = winit::event_loop::EventLoop::new();
+    // Create a window to draw to
+    let window = winit::window::WindowBuilder::new()
+        .with_title("Egui in the world")
+        .build(&event_loop)
+        .unwrap();
+
+    // Create a wgpu instance
+    let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+    let surface = unsafe { instance.create_surface(&window) };
+
+    let mut paint = Painter::for_surface(surface, &instance, 1).block_on().unwrap();
+    let mut winsize = window.inner_size();
+    paint.configure(Size2D::new(winsize.width, winsize.height), wgpu::PresentMode::Fifo, FORMAT);
+
+    let ctx = egui::Context::default();
+    let mut egui = setup(&paint);
+
+    let start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event: win_event, .. } => match win_event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    winsize = size;
+                    paint.configure(Size2D::new(size.width, size.height), wgpu::PresentMode::Fifo, FORMAT);
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => window.request_redraw(),
+            Event::RedrawRequested(_) => {
+                let time = start.elapsed().as_secs_f32();
+
+                // Run egui as if it had a UI_SIZE window of its own
+                let sd = ScreenDescriptor::new([UI_SIZE, UI_SIZE], 1.0);
+                let raw_input = egui::RawInput {
+                    screen_rect: Some(sd.screen_rect()),
+                    pixels_per_point: Some(1.0),
+                    time: Some(time as f64),
+                    ..Default::default()
+                };
+                let full_output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading("A sign in the world");
+                        ui.label("Drawn straight into the scene, scaled and moved like any sprite");
+                        ui.add(egui::ProgressBar::new(time.fract()).animate(true));
+                    });
+                });
+                let clipped_primatives = ctx.tessellate(full_output.shapes);
+
+                // Centre the UI on the origin, flip it to y up, then bob it around the world
+                let half = UI_SIZE as f32 / 2.0;
+                let model = Transform3D::translation(-half, -half, 0.0)
+                    .then_scale(1.0, -1.0, 1.0)
+                    .then_rotate(0.0, 0.0, 1.0, Angle::radians(time.sin() * 0.2))
+                    .then_scale(1.5, 1.5, 1.0)
+                    .then_translate(Vector3D::new(time.cos() * 100.0, 0.0, 0.0));
+                let screen = Size2D::new(winsize.width as f32, winsize.height as f32);
+                egui.set_transform(Some(WorldTransform::pigeon(model, screen)));
+                paint.update_pipeline(&mut egui, (full_output.textures_delta, clipped_primatives, sd));
+
+                let mut frame = paint.frame();
+                let current_surface = paint.current_frame_no_depth().unwrap();
+                {
+                    let mut pass = frame.pass(PassOp::Clear(Rgba::new(0.1, 0.2, 0.3, 1.0)), &current_surface, None);
+                    egui.render(&mut paint, &mut pass);
+                }
+                paint.present(frame);
+            }
+            _ => (),
+        }
+    });
+}
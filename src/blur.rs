@@ -0,0 +1,345 @@
+//! Optional GPU blur for the backdrop behind egui windows.
+//!
+//! egui rasterizes its own shadows into meshes, which is cheap but looks flat. [`BlurPass`] instead
+//! takes an already-rendered image of the scene, blurs it on the GPU and composites the result
+//! back into the target inside the regions the app asks for, so the UI drawn afterwards sits on a
+//! frosted backdrop.
+//!
+//! To blur only behind the shape of a window, including its rounded corners, paint a
+//! [`backdrop_shape`] under it instead of passing regions to [`BlurPass::apply`]. Call
+//! [`BlurPass::blur`] and [`crate::EguiPipe::set_blur_backdrop`] each frame and the pipe samples the
+//! blurred scene for meshes textured with [`BLUR_TEXTURE_ID`].
+//!
+//! ## Performance
+//! A call to [`BlurPass::apply`] with at least one region records four render passes: a downsample
+//! reading every source pixel once, two blur passes each taking 17 samples per half-res pixel, and
+//! a composite limited to the regions' scissor rects. It also keeps two half-res textures in the
+//! source's format alive. On a 1080p target that is roughly 1.1 million extra texture samples per
+//! frame, so only enable it where it is actually visible and skip the call when there are no
+//! regions (which records nothing).
+use crate::{calculate_pixel_rect, ClipRounding, ScreenDescriptor};
+use egui::{
+    epaint::{tessellator::path::rounded_rectangle, Mesh, Rounding, Vertex},
+    pos2, Color32, Rect, Shape, TextureId, Vec2,
+};
+use euclid::Size2D;
+use parrot::{
+    binding::{Binding, BindingGroup, BindingGroupLayout, BindingType},
+    painter::PassOp,
+    transform::ScreenSpace,
+    Painter, RenderPassExtention, Rgba, Sampler, Texture, UniformBuffer,
+};
+
+/// Texture id meshes sample the blurred backdrop through, see [`crate::EguiPipe::set_blur_backdrop`].
+/// Don't register a user texture with this id.
+pub const BLUR_TEXTURE_ID: TextureId = TextureId::User(u64::MAX - 1);
+
+/// A mesh covering `rect` with the blurred backdrop behind it. `screen_size` is the size of the
+/// screen in points, e.g. `ctx.input().screen_rect().size()`, as the uvs map the mesh onto it.
+pub fn backdrop_shape(rect: Rect, rounding: impl Into<Rounding>, screen_size: Vec2) -> Shape {
+    let mut outline = vec![];
+    rounded_rectangle(&mut outline, rect, rounding.into());
+    let vertex = |pos: egui::Pos2| Vertex {
+        pos,
+        uv: pos2(pos.x / screen_size.x, pos.y / screen_size.y),
+        color: Color32::WHITE,
+    };
+
+    // Fan out from the centre, which is inside any rounded rect
+    let mut mesh = Mesh::with_texture(BLUR_TEXTURE_ID);
+    mesh.vertices.push(vertex(rect.center()));
+    mesh.vertices.extend(outline.iter().map(|p| vertex(*p)));
+    let count = outline.len() as u32;
+    for i in 0..count {
+        mesh.add_triangle(0, 1 + i, 1 + (i + 1) % count);
+    }
+    Shape::mesh(mesh)
+}
+
+/// Uniform for a single blur axis
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct BlurUniform {
+    direction: [f32; 2],
+    sigma: f32,
+    // padding as uniform buffers must be at least 16 bytes
+    _padding: u32,
+}
+
+/// Blurs the scene behind app supplied regions. See the [module docs](self) for the cost.
+#[derive(Debug)]
+pub struct BlurPass {
+    downsample: wgpu::RenderPipeline,
+    blur: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+    texture_layout: BindingGroupLayout,
+    sampler: Sampler,
+    /// Half-res ping pong buffers, (re)created when the source size changes
+    buffers: Vec<(Texture, BindingGroup)>,
+    /// Horizontal and vertical blur uniforms
+    axes: Vec<(UniformBuffer, BindingGroup)>,
+    sigma: f32,
+}
+
+impl BlurPass {
+    /// Default standard deviation of the blur in half-res pixels
+    pub const DEFAULT_SIGMA: f32 = 4.0;
+
+    /// Create the blur pass. `format` is the format of both the source texture and the target.
+    pub fn new(paint: &Painter, format: wgpu::TextureFormat) -> Self {
+        let texture_layout = paint.device.create_binding_group_layout(
+            0,
+            &[
+                Binding {
+                    binding: BindingType::Texture {
+                        multisampled: false,
+                    },
+                    stage: wgpu::ShaderStages::FRAGMENT,
+                },
+                Binding {
+                    binding: BindingType::Sampler,
+                    stage: wgpu::ShaderStages::FRAGMENT,
+                },
+            ],
+            Some("Egui blur texture bind group"),
+        );
+        let uniform_layout = paint.device.create_binding_group_layout(
+            1,
+            &[Binding {
+                binding: BindingType::UniformBuffer,
+                stage: wgpu::ShaderStages::FRAGMENT,
+            }],
+            Some("Egui blur uniform bind group"),
+        );
+        let shader = paint
+            .device
+            .create_wgsl_shader(include_str!("./blur.wgsl"), Some("Egui blur shader"));
+
+        let downsample = fullscreen_pipeline(
+            paint,
+            &[&texture_layout.wgpu],
+            &shader.wgpu,
+            "fs_downsample",
+            format,
+            Some("Egui blur downsample"),
+        );
+        let blur = fullscreen_pipeline(
+            paint,
+            &[&texture_layout.wgpu, &uniform_layout.wgpu],
+            &shader.wgpu,
+            "fs_blur",
+            format,
+            Some("Egui blur"),
+        );
+        let composite = fullscreen_pipeline(
+            paint,
+            &[&texture_layout.wgpu],
+            &shader.wgpu,
+            "fs_composite",
+            format,
+            Some("Egui blur composite"),
+        );
+
+        let sampler = paint.sampler(
+            wgpu::FilterMode::Linear,
+            wgpu::FilterMode::Linear,
+            Some("Egui blur sampler"),
+        );
+        let axes = (0..2)
+            .map(|_| {
+                let buffer = paint
+                    .uniform_buffer(&[BlurUniform::default()], Some("Egui blur uniform buffer"));
+                let binding = paint.binding_group(
+                    &uniform_layout,
+                    &[&buffer],
+                    Some("Egui blur uniform binding group"),
+                );
+                (buffer, binding)
+            })
+            .collect();
+
+        Self {
+            downsample,
+            blur,
+            composite,
+            texture_layout,
+            sampler,
+            buffers: vec![],
+            axes,
+            sigma: Self::DEFAULT_SIGMA,
+        }
+    }
+
+    /// Set the standard deviation of the blur in half-res pixels. Clamped to the 8 taps the shader takes either side.
+    pub fn set_sigma(&mut self, sigma: f32) {
+        self.sigma = sigma.clamp(0.5, 8.0);
+    }
+
+    /// Blur `source` and composite it into `target` inside each of `regions` (in points).
+    ///
+    /// `source` is what has been rendered so far and must have been created with
+    /// [`wgpu::TextureUsages::TEXTURE_BINDING`], e.g. a [`parrot::FrameBuffer`]. It can't be the view
+    /// passed as `target`. Call this before rendering egui so the windows are drawn on top.
+    pub fn apply(
+        &mut self,
+        paint: &Painter,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &Texture,
+        target: &wgpu::TextureView,
+        regions: &[egui::Rect],
+        screen: &ScreenDescriptor,
+    ) {
+        if regions.is_empty() {
+            return;
+        }
+        self.blur(paint, encoder, source);
+
+        // Composite the blurred image behind each region
+        let mut pass = wgpu::RenderPass::begin(encoder, target, None, None, PassOp::Load());
+        pass.set_pipeline(&self.composite);
+        pass.set_binding(&self.buffers[0].1, &[]);
+        for region in regions {
+            let rect = calculate_pixel_rect(
+                region,
+                screen.scale_factors(),
+                screen.size_in_pixels,
+                ClipRounding::Round,
+            );
+            if !rect.is_empty() {
+                pass.set_scissor_rect(rect.origin.x, rect.origin.y, rect.width(), rect.height());
+                pass.draw(0..3, 0..1);
+            }
+        }
+    }
+
+    /// Blur `source` into a half-res texture without compositing it, for [`BlurPass::blurred`].
+    /// Records the first three of [`BlurPass::apply`]'s passes.
+    pub fn blur(&mut self, paint: &Painter, encoder: &mut wgpu::CommandEncoder, source: &Texture) {
+        let half: Size2D<u32, ScreenSpace> = Size2D::new(
+            (source.size.width / 2).max(1),
+            (source.size.height / 2).max(1),
+        );
+        if self.buffers.first().map(|b| b.0.size) != Some(half) {
+            self.resize(paint, half, source.format);
+        }
+
+        // Point the blur uniforms at the texel size of the buffers
+        let directions = [
+            [1.0 / half.width as f32, 0.0],
+            [0.0, 1.0 / half.height as f32],
+        ];
+        for (axis, direction) in self.axes.iter_mut().zip(directions) {
+            let uniform = BlurUniform {
+                direction,
+                sigma: self.sigma,
+                _padding: Default::default(),
+            };
+            paint.device.update_buffer(&[uniform], &mut axis.0);
+        }
+
+        let source_binding = paint.binding_group(
+            &self.texture_layout,
+            &[source, &self.sampler],
+            Some("Egui blur source binding group"),
+        );
+
+        // Downsample into the first buffer, then blur it horizontally into the second and back
+        let steps = [
+            (&self.downsample, &source_binding, None, 0),
+            (&self.blur, &self.buffers[0].1, Some(&self.axes[0].1), 1),
+            (&self.blur, &self.buffers[1].1, Some(&self.axes[1].1), 0),
+        ];
+        for (pipeline, input, axis, output) in steps {
+            let mut pass = wgpu::RenderPass::begin(
+                encoder,
+                &self.buffers[output].0.view,
+                None,
+                None,
+                PassOp::Clear(Rgba::TRANSPARENT),
+            );
+            pass.set_pipeline(pipeline);
+            pass.set_binding(input, &[]);
+            if let Some(axis) = axis {
+                pass.set_binding(axis, &[]);
+            }
+            pass.draw(0..3, 0..1);
+        }
+    }
+
+    /// The result of the last [`BlurPass::blur`], if it has run
+    pub fn blurred(&self) -> Option<&Texture> {
+        self.buffers.first().map(|b| &b.0)
+    }
+
+    // Create the half-res buffers
+    fn resize(
+        &mut self,
+        paint: &Painter,
+        size: Size2D<u32, ScreenSpace>,
+        format: wgpu::TextureFormat,
+    ) {
+        log::debug!("Resizing egui blur buffers >> Size: {:?}", size);
+        self.buffers = (0..2)
+            .map(|i| {
+                let tex = paint.texture(
+                    size,
+                    format,
+                    wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    Some(format!("Egui blur buffer {}", i).as_str()),
+                    false,
+                );
+                let binding = paint.binding_group(
+                    &self.texture_layout,
+                    &[&tex, &self.sampler],
+                    Some(format!("Egui blur buffer {} binding group", i).as_str()),
+                );
+                (tex, binding)
+            })
+            .collect();
+    }
+}
+
+// Pipeline drawing a single fullscreen triangle with no vertex buffer
+fn fullscreen_pipeline(
+    paint: &Painter,
+    b_layouts: &[&wgpu::BindGroupLayout],
+    shader: &wgpu::ShaderModule,
+    fs_entry: &str,
+    format: wgpu::TextureFormat,
+    name: Option<&str>,
+) -> wgpu::RenderPipeline {
+    let layout = paint
+        .device
+        .wgpu
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: name,
+            bind_group_layouts: b_layouts,
+            push_constant_ranges: &[],
+        });
+
+    paint
+        .device
+        .wgpu
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: name,
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: fs_entry,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+}
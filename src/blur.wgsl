@@ -0,0 +1,56 @@
+// Fullscreen vertex shader
+
+struct FullscreenOutput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    var out: FullscreenOutput;
+    // A single triangle covering the whole target
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shaders
+
+struct Blur {
+    // Step between taps in uv space, pointing along the blur axis
+    direction: vec2<f32>,
+    // Standard deviation of the gaussian in taps
+    sigma: f32,
+    _padding: u32,
+}
+
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(1) @binding(0) var<uniform> r_blur: Blur;
+
+// Halves the source. The linear sampler averages the 2x2 block under each texel
+@fragment
+fn fs_downsample(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, in.uv);
+}
+
+// One axis of a separable gaussian blur
+@fragment
+fn fs_blur(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    var color = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i: i32 = -8; i <= 8; i = i + 1) {
+        let x = f32(i);
+        let weight = exp(-(x * x) / (2.0 * r_blur.sigma * r_blur.sigma));
+        color = color + weight * textureSample(r_tex_color, r_tex_sampler, in.uv + x * r_blur.direction);
+        total = total + weight;
+    }
+    return color / total;
+}
+
+// Upscales the blurred buffer back onto the target
+@fragment
+fn fs_composite(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, in.uv);
+}
@@ -0,0 +1,540 @@
+use egui::epaint::PaintCallbackInfo;
+use euclid::Transform3D;
+use parrot::{
+    binding::BindingGroup,
+    buffers::{IndexBuffer, UniformBuffer, VertexBuffer},
+    pipeline::Blending,
+    transform::{ScreenSpace, WorldSpace},
+    Painter, RenderPassExtention,
+};
+use pigeon::{
+    pigeon::{Container, OPENGL_TO_WGPU_MATRIX},
+    pipeline::{QuadPipe, TrianglePipe},
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Weak},
+};
+
+/// Custom drawing inside the UI, added to egui with [`paint_callback`]. Gets the painter both when
+/// the pipe prepares, to upload resources, and when it renders, to draw into egui's pass.
+pub trait EguiCallback: Send + Sync {
+    /// Called from `prepare` before anything is drawn, e.g. to create pipelines and write buffers
+    /// kept in `resources`
+    fn prepare(
+        &self,
+        _paint: &mut Painter,
+        _info: &PaintCallbackInfo,
+        _resources: &mut CallbackResources,
+    ) {
+    }
+
+    /// Draw into the pass egui is rendered into, in order with the rest of the UI. The viewport
+    /// is set to `info.viewport` and the scissor to `info.clip_rect`. Set the pipeline and
+    /// bindings you need; the pipe sets its own again afterwards.
+    fn paint<'a>(
+        &'a self,
+        info: &PaintCallbackInfo,
+        paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        resources: &'a CallbackResources,
+    );
+}
+
+/// Wrap `callback` in an [`egui::PaintCallback`] drawn in `rect`, for [`egui::Painter::add`].
+/// Paint callbacks created any other way are skipped by [`crate::EguiPipe`].
+pub fn paint_callback(
+    rect: egui::Rect,
+    callback: impl EguiCallback + 'static,
+) -> egui::PaintCallback {
+    let callback: Arc<dyn EguiCallback> = Arc::new(callback);
+    egui::PaintCallback {
+        rect,
+        // The pipe calls this with a slot to get the callback back out of egui
+        callback: Arc::new(move |_, slot| {
+            if let Some(slot) = slot.downcast_mut::<CallbackSlot>() {
+                slot.0 = Some(callback.clone());
+            }
+        }),
+    }
+}
+
+// What egui's callback is given to hand over the EguiCallback
+#[derive(Default)]
+pub(crate) struct CallbackSlot(pub(crate) Option<Arc<dyn EguiCallback>>);
+
+/// Resources shared by every callback, one value per type, e.g. a pipeline and its buffers.
+/// Values are looked up by their type, so callbacks of different kinds can keep their own state in
+/// the same pipe without capturing it, and share what they have in common.
+///
+/// ```no_run
+/// # use egui_pigeon::{paint_callback, CallbackFn, EguiPipe};
+/// struct Time(f32);
+/// struct Spinner(wgpu::RenderPipeline);
+/// struct Graph(wgpu::RenderPipeline);
+///
+/// # fn ui(pipe: &mut EguiPipe, ui: &mut egui::Ui, spinner: Spinner, graph: Graph, rect: egui::Rect) {
+/// pipe.callback_resources().insert(Time(0.0));
+/// pipe.callback_resources().insert(spinner);
+/// pipe.callback_resources().insert(graph);
+///
+/// // Both kinds read the shared time, and each finds its own pipeline
+/// let spin = CallbackFn::default().paint(|_, pass, resources| {
+///     if let (Some(Time(_t)), Some(Spinner(pipeline))) = (resources.get(), resources.get()) {
+///         pass.set_pipeline(pipeline);
+///     }
+/// });
+/// let plot = CallbackFn::default().paint(|_, pass, resources| {
+///     if let (Some(Time(_t)), Some(Graph(pipeline))) = (resources.get(), resources.get()) {
+///         pass.set_pipeline(pipeline);
+///     }
+/// });
+/// ui.painter().add(paint_callback(rect, spin));
+/// ui.painter().add(paint_callback(rect, plot));
+/// # }
+/// ```
+#[derive(Default)]
+pub struct CallbackResources(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl CallbackResources {
+    /// Store `value`, replacing any other value of its type
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        self.0.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    /// The stored value of type `T`
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.0.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// The stored value of type `T`
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.0.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Take the stored value of type `T` out
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.0
+            .remove(&TypeId::of::<T>())?
+            .downcast()
+            .ok()
+            .map(|b| *b)
+    }
+}
+
+impl std::fmt::Debug for CallbackResources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackResources")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// An [`EguiCallback`] made of closures, for callbacks keeping everything in [`CallbackResources`]
+pub struct CallbackFn {
+    prepare: Box<PrepareFn>,
+    paint: Box<PaintFn>,
+}
+type PrepareFn = dyn Fn(&mut Painter, &PaintCallbackInfo, &mut CallbackResources) + Send + Sync;
+type PaintFn = dyn for<'a> Fn(&PaintCallbackInfo, &mut wgpu::RenderPass<'a>, &'a CallbackResources)
+    + Send
+    + Sync;
+
+impl CallbackFn {
+    /// A callback running `prepare` from `prepare` and `paint` from `render`. Either can be
+    /// replaced later, and [`CallbackFn::default`] does nothing in both.
+    pub fn new(
+        prepare: impl Fn(&mut Painter, &PaintCallbackInfo, &mut CallbackResources)
+            + Send
+            + Sync
+            + 'static,
+        paint: impl for<'a> Fn(&PaintCallbackInfo, &mut wgpu::RenderPass<'a>, &'a CallbackResources)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            prepare: Box::new(prepare),
+            paint: Box::new(paint),
+        }
+    }
+
+    /// Set what is run from `prepare`, see [`EguiCallback::prepare`]
+    pub fn prepare(
+        mut self,
+        prepare: impl Fn(&mut Painter, &PaintCallbackInfo, &mut CallbackResources)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.prepare = Box::new(prepare);
+        self
+    }
+
+    /// Set what is run from `render`, see [`EguiCallback::paint`]
+    pub fn paint(
+        mut self,
+        paint: impl for<'a> Fn(&PaintCallbackInfo, &mut wgpu::RenderPass<'a>, &'a CallbackResources)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.paint = Box::new(paint);
+        self
+    }
+}
+
+impl Default for CallbackFn {
+    fn default() -> Self {
+        Self::new(|_, _, _| (), |_, _, _| ())
+    }
+}
+
+impl EguiCallback for CallbackFn {
+    fn prepare(
+        &self,
+        paint: &mut Painter,
+        info: &PaintCallbackInfo,
+        resources: &mut CallbackResources,
+    ) {
+        (self.prepare)(paint, info, resources)
+    }
+
+    fn paint<'a>(
+        &'a self,
+        info: &PaintCallbackInfo,
+        _paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        resources: &'a CallbackResources,
+    ) {
+        (self.paint)(info, pass, resources)
+    }
+}
+
+/// An [`EguiCallback`] drawing pigeon's shapes, added to a [`Container`] by `add` on every
+/// prepare. The shapes are in pixels from the center of the callback's rect, y up, the same as
+/// pigeon draws them on the screen. Only the triangles and textured quads of the container are
+/// drawn, with the pipelines in [`ContainerPipes`].
+///
+/// ```no_run
+/// # use egui_pigeon::ContainerCallback;
+/// # use pigeon_2d::{graphics::{Rectangle, Rgba}, pigeon::add_triangle};
+/// # fn ui(ui: &mut egui::Ui, rect: egui::Rect) {
+/// let square = Rectangle::new((0.0, 0.0, 0.0), (20.0, 20.0), Rgba::RED);
+/// let callback = ContainerCallback::new(move |container| add_triangle(container, vec![&square]));
+/// ui.painter().add(callback.into_paint_callback(rect));
+/// # }
+/// ```
+pub struct ContainerCallback {
+    add: Box<AddFn>,
+    // Finds the shapes this callback uploaded among the ones in ContainerPipes
+    token: Arc<()>,
+}
+type AddFn = dyn Fn(&mut Container) + Send + Sync;
+
+impl ContainerCallback {
+    /// A callback drawing the shapes `add` puts in the container
+    pub fn new(add: impl Fn(&mut Container) + Send + Sync + 'static) -> Self {
+        Self {
+            add: Box::new(add),
+            token: Arc::new(()),
+        }
+    }
+
+    /// The callback in an [`egui::PaintCallback`] drawn in `rect`, see [`paint_callback`]
+    pub fn into_paint_callback(self, rect: egui::Rect) -> egui::PaintCallback {
+        paint_callback(rect, self)
+    }
+}
+
+impl EguiCallback for ContainerCallback {
+    fn prepare(
+        &self,
+        paint: &mut Painter,
+        info: &PaintCallbackInfo,
+        resources: &mut CallbackResources,
+    ) {
+        let mut container = Container::new();
+        (self.add)(&mut container);
+        if resources.get::<ContainerPipes>().is_none() {
+            let pipes = ContainerPipes::new(paint, paint.preferred_format());
+            resources.insert(pipes);
+        }
+        if let Some(pipes) = resources.get_mut::<ContainerPipes>() {
+            pipes.upload(paint, info, container, &self.token);
+        }
+    }
+
+    fn paint<'a>(
+        &'a self,
+        _info: &PaintCallbackInfo,
+        _paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        resources: &'a CallbackResources,
+    ) {
+        if let Some(pipes) = resources.get::<ContainerPipes>() {
+            pipes.draw(pass, &self.token);
+        }
+    }
+}
+
+/// The pipelines [`ContainerCallback`] draws with, kept in [`CallbackResources`]. The first
+/// prepare creates them for the painter's preferred format; insert ones made with
+/// [`ContainerPipes::new`] for a pipe with another [`crate::EguiPipe::target_format`].
+pub struct ContainerPipes {
+    /// Draws the textured quads. Textures are bound the first time a quad uses them.
+    pub quad: QuadPipe,
+    /// Draws the triangles
+    pub triangle: TrianglePipe,
+    // What each callback alive uploaded. The pipes' own buffers and transform are shared, so
+    // every callback gets its own.
+    shapes: Vec<Shapes>,
+}
+
+// The buffers of a ContainerCallback, drawn in its paint
+struct Shapes {
+    owner: Weak<()>,
+    _transform: UniformBuffer,
+    quad_transform: BindingGroup,
+    triangle_transform: BindingGroup,
+    quads: Option<(VertexBuffer, IndexBuffer, Vec<QuadGroup>)>,
+    triangles: Option<(VertexBuffer, IndexBuffer, u32)>,
+}
+
+// Index range of quads sharing a texture, with that texture's id
+type QuadGroup = (Range<u32>, usize);
+
+impl ContainerPipes {
+    /// Create pigeon's pipelines drawing into targets of `format`. The pass has no depth
+    /// attachment, so shapes are drawn in the order they were added.
+    pub fn new(paint: &Painter, format: wgpu::TextureFormat) -> Self {
+        Self {
+            quad: paint.pipeline_no_depth(Blending::default(), format, Some("Egui quad shader")),
+            triangle: paint.pipeline_no_depth(
+                Blending::default(),
+                format,
+                Some("Egui triangle shader"),
+            ),
+            shapes: vec![],
+        }
+    }
+
+    // Upload the shapes in `container` for the callback holding `token`
+    fn upload(
+        &mut self,
+        paint: &Painter,
+        info: &PaintCallbackInfo,
+        container: Container,
+        token: &Arc<()>,
+    ) {
+        // Shapes of callbacks that were dropped aren't drawn again
+        self.shapes
+            .retain(|s| s.owner.strong_count() > 0 && !s.owner.ptr_eq(&Arc::downgrade(token)));
+
+        let [w, h] = [
+            info.viewport.width() * info.pixels_per_point,
+            info.viewport.height() * info.pixels_per_point,
+        ];
+        let ortho: Transform3D<f32, WorldSpace, ScreenSpace> =
+            Transform3D::ortho(-w / 2.0, w / 2.0, -h / 2.0, h / 2.0, 50.0, -50.0);
+        let ortho = OPENGL_TO_WGPU_MATRIX.then(&ortho);
+        let transform = paint.uniform_buffer(
+            &[ortho.to_arrays()],
+            Some("Egui container transform buffer"),
+        );
+        let quad_transform = paint.binding_group(
+            &self.quad.pipeline.layout.b_layouts[1],
+            &[&transform],
+            Some("Egui container quad transform"),
+        );
+        let triangle_transform = paint.binding_group(
+            &self.triangle.pipeline.layout.b_layouts[0],
+            &[&transform],
+            Some("Egui container triangle transform"),
+        );
+
+        let (mut vertices, mut indices, mut groups) = (vec![], vec![], vec![]);
+        for mut quad in container.quad {
+            let tex = match quad.texture {
+                Some(tex) => tex,
+                None => {
+                    log::warn!("Skipping a container quad >> No texture");
+                    continue;
+                }
+            };
+            if !self.quad.texture_binds.contains_key(&tex.id) {
+                self.quad.add_texture(paint, &tex);
+            }
+            let (start, first) = (vertices.len() as u16, indices.len() as u32);
+            vertices.append(&mut quad.vertices);
+            indices.extend(quad.indicies.iter().map(|i| i + start));
+            groups.push((first..indices.len() as u32, tex.id));
+        }
+        let quads = (!indices.is_empty()).then(|| {
+            (
+                paint.vertex_buffer(&vertices, Some("Egui container quad vertices")),
+                paint.index_buffer(&indices, Some("Egui container quad indices")),
+                groups,
+            )
+        });
+
+        let (mut vertices, mut indices) = (vec![], vec![]);
+        for mut tri in container.triangle {
+            let start = vertices.len() as u16;
+            vertices.append(&mut tri.vertices);
+            indices.extend(tri.indicies.iter().map(|i| i + start));
+        }
+        let triangles = (!indices.is_empty()).then(|| {
+            (
+                paint.vertex_buffer(&vertices, Some("Egui container triangle vertices")),
+                paint.index_buffer(&indices, Some("Egui container triangle indices")),
+                indices.len() as u32,
+            )
+        });
+
+        self.shapes.push(Shapes {
+            owner: Arc::downgrade(token),
+            _transform: transform,
+            quad_transform,
+            triangle_transform,
+            quads,
+            triangles,
+        });
+    }
+
+    // Draw the shapes uploaded for the callback holding `token`
+    fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, token: &Arc<()>) {
+        let token = Arc::downgrade(token);
+        let shapes = match self.shapes.iter().find(|s| s.owner.ptr_eq(&token)) {
+            Some(s) => s,
+            None => return,
+        };
+        if let Some((vertices, indices, count)) = &shapes.triangles {
+            pass.set_pipeline(&self.triangle.pipeline.wgpu);
+            pass.set_binding(&shapes.triangle_transform, &[]);
+            pass.set_parrot_vertex_buffer(vertices);
+            pass.set_parrot_index_buffer(indices);
+            pass.draw_parrot_indexed(0..*count, 0..1);
+        }
+        if let Some((vertices, indices, groups)) = &shapes.quads {
+            pass.set_pipeline(&self.quad.pipeline.wgpu);
+            pass.set_binding(&shapes.quad_transform, &[]);
+            pass.set_parrot_vertex_buffer(vertices);
+            pass.set_parrot_index_buffer(indices);
+            for (range, tex_id) in groups {
+                if let Some(binding) = self.quad.texture_binds.get(tex_id) {
+                    pass.set_binding(binding, &[]);
+                    pass.draw_parrot_indexed(range.clone(), 0..1);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ContainerPipes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContainerPipes")
+            .field("shapes", &self.shapes.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn both_closures_run_through_the_pipe() {
+        use crate::{setup, ColorSpace, ScreenDescriptor};
+        use egui::{epaint::Primitive, pos2, vec2, ClippedPrimitive};
+
+        let mut paint = match crate::tests::test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        pipe.callback_resources().insert(7u32);
+
+        let prepared = Arc::new(AtomicUsize::new(0));
+        let painted = Arc::new(AtomicUsize::new(0));
+        let (p, q) = (prepared.clone(), painted.clone());
+        let callback = CallbackFn::new(
+            move |_, _, resources| {
+                assert_eq!(resources.get::<u32>(), Some(&7));
+                p.fetch_add(1, Ordering::Relaxed);
+            },
+            move |info, _, resources| {
+                assert_eq!(info.screen_size_px, [4, 4]);
+                assert!(resources.get::<u32>().is_some());
+                q.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+        let rect = egui::Rect::from_min_size(pos2(0.0, 0.0), vec2(4.0, 4.0));
+        let primitives = vec![ClippedPrimitive {
+            clip_rect: rect,
+            primitive: Primitive::Callback(paint_callback(rect, callback)),
+        }];
+        let screen = ScreenDescriptor::new([4, 4], 1.0);
+        paint.update_pipeline(&mut pipe, (Default::default(), primitives, screen));
+        assert_eq!(prepared.load(Ordering::Relaxed), 1);
+        assert_eq!(painted.load(Ordering::Relaxed), 0);
+
+        let target = paint.texture(
+            euclid::Size2D::new(4, 4),
+            ColorSpace::SrgbTarget.target_format(),
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            None,
+            false,
+        );
+        pipe.render_layer(&mut paint, &target);
+        assert_eq!(prepared.load(Ordering::Relaxed), 1);
+        assert_eq!(painted.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn containers_are_drawn_in_the_callback_rect() {
+        use crate::{read_back, setup, ColorSpace, ScreenDescriptor, FONT_ATLAS_ID};
+        use egui::{epaint::ImageDelta, pos2, vec2, ClippedPrimitive, Color32, ColorImage};
+        use pigeon::{
+            graphics::{Rectangle, Rgba},
+            pigeon::add_triangle,
+        };
+
+        let mut paint = match crate::tests::test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let mut textures = egui::TexturesDelta::default();
+        textures.set.insert(
+            FONT_ATLAS_ID,
+            ImageDelta::full(ColorImage::new([1, 1], Color32::WHITE)),
+        );
+        // A square filling the left half of the target
+        let square = Rectangle::new((0.0, 0.0, 0.0), (4.0, 8.0), Rgba::RED);
+        let callback = ContainerCallback::new(move |c| add_triangle(c, vec![&square]));
+        let rect = egui::Rect::from_min_size(pos2(0.0, 0.0), vec2(4.0, 8.0));
+        let primitives = vec![ClippedPrimitive {
+            clip_rect: egui::Rect::from_min_size(pos2(0.0, 0.0), vec2(8.0, 8.0)),
+            primitive: egui::epaint::Primitive::Callback(callback.into_paint_callback(rect)),
+        }];
+        let screen = ScreenDescriptor::new([8, 8], 1.0);
+        paint.update_pipeline(&mut pipe, (textures, primitives, screen));
+
+        let target = paint.texture(
+            euclid::Size2D::new(8, 8),
+            ColorSpace::SrgbTarget.target_format(),
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            None,
+            false,
+        );
+        pipe.render_layer(&mut paint, &target);
+        let pixels = read_back(&paint, &target, "Container test readback").unwrap();
+        assert_eq!(pixels[4 * 8 + 1], [255, 0, 0, 255]);
+        assert_eq!(pixels[4 * 8 + 6], [0, 0, 0, 0]);
+        assert!(pipe.take_errors().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! A cursor drawn by [`crate::EguiPipe`] above the UI, for when the hardware cursor is hidden.
+//!
+//! Enable it with [`crate::EguiPipe::set_software_cursor`] and pass the pointer position and
+//! egui's [`CursorIcon`] to [`crate::EguiPipe::set_cursor`] every frame. The sprites come from a
+//! [`CursorSheet`], which defaults to a small built in arrow and text cursor; supply your own to
+//! match the game's art.
+use egui::{epaint::Mesh, pos2, vec2, Color32, ColorImage, CursorIcon, Pos2, Rect, TextureId};
+
+/// Texture id the cursor sheet is uploaded under. Don't register a user texture with this id.
+pub const CURSOR_TEXTURE_ID: TextureId = TextureId::User(u64::MAX);
+
+/// A cursor image in a [`CursorSheet`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CursorSprite {
+    /// Rect of the sprite in the sheet in pixels. It is drawn one point per pixel.
+    pub rect: Rect,
+    /// Offset from the top left of the sprite to the point placed at the pointer, in points
+    pub hotspot: egui::Vec2,
+}
+
+/// Image holding every cursor sprite and where each [`CursorIcon`] is in it
+#[derive(Clone)]
+pub struct CursorSheet {
+    /// Image holding the sprites
+    pub image: ColorImage,
+    /// The sprite for each icon. Icons without one use [`CursorIcon::Default`]'s.
+    pub sprites: Vec<(CursorIcon, CursorSprite)>,
+}
+
+impl CursorSheet {
+    /// The sprite drawn for `icon`, if any
+    pub fn sprite(&self, icon: CursorIcon) -> Option<CursorSprite> {
+        if icon == CursorIcon::None {
+            return None;
+        }
+        let find = |icon| {
+            self.sprites
+                .iter()
+                .find(|(i, _)| *i == icon)
+                .map(|(_, s)| *s)
+        };
+        find(icon).or_else(|| find(CursorIcon::Default))
+    }
+
+    // Quad for `icon` with its hotspot at `pos`, in points
+    pub(crate) fn mesh(&self, icon: CursorIcon, pos: Pos2) -> Option<Mesh> {
+        let sprite = self.sprite(icon)?;
+        let [w, h] = self.image.size;
+        let uv = Rect::from_min_max(
+            pos2(sprite.rect.min.x / w as f32, sprite.rect.min.y / h as f32),
+            pos2(sprite.rect.max.x / w as f32, sprite.rect.max.y / h as f32),
+        );
+        let mut mesh = Mesh::with_texture(CURSOR_TEXTURE_ID);
+        mesh.add_rect_with_uv(
+            Rect::from_min_size(pos - sprite.hotspot, sprite.rect.size()),
+            uv,
+            Color32::WHITE,
+        );
+        Some(mesh)
+    }
+}
+
+// The default arrow, '#' for black and 'o' for white
+const ARROW: [&str; 19] = [
+    "#...........",
+    "##..........",
+    "#o#.........",
+    "#oo#........",
+    "#ooo#.......",
+    "#oooo#......",
+    "#ooooo#.....",
+    "#oooooo#....",
+    "#ooooooo#...",
+    "#oooooooo#..",
+    "#ooooooooo#.",
+    "#oooooo#####",
+    "#ooo#oo#....",
+    "#oo##oo#....",
+    "#o#..#oo#...",
+    "##...#oo#...",
+    "#.....#oo#..",
+    "......#oo#..",
+    ".......##...",
+];
+
+impl Default for CursorSheet {
+    /// A black and white arrow and text cursor
+    fn default() -> Self {
+        let mut image = ColorImage::new([24, 20], Color32::TRANSPARENT);
+        let width = image.size[0];
+        let mut set = |x: usize, y: usize, color| image.pixels[y * width + x] = color;
+
+        // White arrow with a black border
+        for (y, row) in ARROW.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => set(x, y, Color32::BLACK),
+                    'o' => set(x, y, Color32::WHITE),
+                    _ => (),
+                }
+            }
+        }
+
+        // Black I-beam with a white outline
+        let beam = |x: i32, y: i32| {
+            (x == 3 && (1..15).contains(&y)) || ((y == 1 || y == 14) && (1..6).contains(&x))
+        };
+        for y in 0..16 {
+            for x in 0..7 {
+                let color = if beam(x, y) {
+                    Color32::BLACK
+                } else if (-1..=1).any(|dx| (-1..=1).any(|dy| beam(x + dx, y + dy))) {
+                    Color32::WHITE
+                } else {
+                    continue;
+                };
+                set(16 + x as usize, y as usize, color);
+            }
+        }
+
+        let arrow = CursorSprite {
+            rect: Rect::from_min_size(pos2(0.0, 0.0), vec2(12.0, 19.0)),
+            hotspot: vec2(0.5, 0.5),
+        };
+        let text = CursorSprite {
+            rect: Rect::from_min_size(pos2(16.0, 0.0), vec2(7.0, 16.0)),
+            hotspot: vec2(3.5, 8.0),
+        };
+        Self {
+            image,
+            sprites: vec![(CursorIcon::Default, arrow), (CursorIcon::Text, text)],
+        }
+    }
+}
+
+impl std::fmt::Debug for CursorSheet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CursorSheet")
+            .field("size", &self.image.size)
+            .field("sprites", &self.sprites)
+            .finish()
+    }
+}
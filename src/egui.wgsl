@@ -2,12 +2,47 @@
 
 struct VertexOutput {
     @location(0) tex_coord: vec2<f32>,
+    // Unconverted sRGB color, 0-255
     @location(1) color: vec4<f32>,
     @builtin(position) position: vec4<f32>,
 }
 
 struct Locals {
     screen_size: vec2<f32>,
+    // 1 when the target isn't sRGB and blending happens in gamma space
+    linear_target: u32,
+    // Multiplies the output color
+    brightness: f32,
+    // Gamma applied to the output color
+    gamma: f32,
+    // Depth of the first group for vs_layered
+    max_depth: f32,
+    // Number of groups vs_layered spreads over the depth range
+    layers: u32,
+    // 1 to round untextured vertices to whole pixels
+    pixel_snap: u32,
+    // Premultiplied color the whole UI is multiplied by
+    ui_tint: vec4<f32>,
+    // Size of the render target in physical pixels
+    screen_size_in_pixels: vec2<f32>,
+    // Columns of the matrix turning the UI on the target. A mat2x2 is laid out differently by
+    // naga's GL backend, shifting the fields after it.
+    rotation_x: vec2<f32>,
+    rotation_y: vec2<f32>,
+    // 1 to place the UI with transform instead of over the target
+    transformed: u32,
+    // Seconds since the pipe was created, or as set, wrapping every hour
+    time: f32,
+    // Takes points to clip space when transformed
+    transform: mat4x4<f32>,
+    // 1 for RGB and 2 for BGR subpixel text, 0 for grayscale
+    subpixel: u32,
+    // Scales the alpha ramp at the edges of shapes
+    feather: f32,
+    // Points the whole UI is moved by
+    displacement: vec2<f32>,
+    // Scales the UI around the middle of the screen
+    zoom: f32,
 }
 @group(0) @binding(0) var<uniform> r_locals: Locals;
 
@@ -29,41 +64,60 @@ fn unpack_color(color: u32) -> vec4<f32> {
     );
 }
 
+fn vertex(a_pos: vec2<f32>, a_tex_coord: vec2<f32>, a_color: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = a_tex_coord;
+    out.color = unpack_color(a_color);
+    let center = r_locals.screen_size / 2.0;
+    var pos = center + (a_pos - center) * r_locals.zoom + r_locals.displacement;
+    if (r_locals.transformed == 1u) {
+        out.position = r_locals.transform * vec4<f32>(pos, 0.0, 1.0);
+        return out;
+    }
+    // Shapes use the white pixel at uv 0, glyphs and images are left where egui put them
+    if (r_locals.pixel_snap == 1u && all(a_tex_coord == vec2<f32>(0.0))) {
+        let scale = r_locals.screen_size_in_pixels / r_locals.screen_size;
+        pos = round(pos * scale) / scale;
+    }
+    let clip = vec2<f32>(
+        2.0 * pos.x / r_locals.screen_size.x - 1.0,
+        1.0 - 2.0 * pos.y / r_locals.screen_size.y,
+    );
+    out.position = vec4<f32>(mat2x2<f32>(r_locals.rotation_x, r_locals.rotation_y) * clip, 0.0, 1.0);
+    return out;
+}
+
 @vertex
 fn vs_main(
     @location(0) a_pos: vec2<f32>,
     @location(1) a_tex_coord: vec2<f32>,
     @location(2) a_color: u32,
 ) -> VertexOutput {
-    var out: VertexOutput;
-    out.tex_coord = a_tex_coord;
-    let color = unpack_color(a_color);
-    out.color = vec4<f32>(linear_from_srgb(color.rgb), color.a / 255.0);
-    out.position = vec4<f32>(
-        2.0 * a_pos.x / r_locals.screen_size.x - 1.0,
-        1.0 - 2.0 * a_pos.y / r_locals.screen_size.y,
-        0.0,
-        1.0,
-    );
-    return out;
+    return vertex(a_pos, a_tex_coord, a_color);
 }
 
+// vs_main at a depth given by the group drawn, later groups nearer, for the depth prepass
 @vertex
-fn vs_conv_main(
+fn vs_layered(
     @location(0) a_pos: vec2<f32>,
     @location(1) a_tex_coord: vec2<f32>,
     @location(2) a_color: u32,
+    @builtin(instance_index) layer: u32,
 ) -> VertexOutput {
+    var out = vertex(a_pos, a_tex_coord, a_color);
+    let remaining = f32(r_locals.layers) - f32(layer);
+    out.position.z = r_locals.max_depth * remaining / f32(r_locals.layers + 1u);
+    return out;
+}
+
+// Fullscreen triangle covering a whole texture, used to composite offscreen targets with fs_blit
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coord = a_tex_coord;
-    let color = unpack_color(a_color);
-    out.color = vec4<f32>(color.rgba / 255.0);
-    out.position = vec4<f32>(
-        2.0 * a_pos.x / r_locals.screen_size.x - 1.0,
-        1.0 - 2.0 * a_pos.y / r_locals.screen_size.y,
-        0.0,
-        1.0,
-    );
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.tex_coord = uv;
+    out.color = vec4<f32>(255.0);
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
     return out;
 }
 
@@ -72,7 +126,119 @@ fn vs_conv_main(
 @group(1) @binding(0) var r_tex_color: texture_2d<f32>;
 @group(1) @binding(1) var r_tex_sampler: sampler;
 
+// The vertex color in the target's color space
+fn vertex_color(in: VertexOutput) -> vec4<f32> {
+    // An sRGB target expects linear colors, otherwise egui's gamma colors are written as is
+    if (r_locals.linear_target == 1u) {
+        return in.color / 255.0;
+    }
+    return vec4<f32>(linear_from_srgb(in.color.rgb), in.color.a / 255.0);
+}
+
+// Apply the color adjustments and tint to a premultiplied color
+fn adjust(color: vec4<f32>) -> vec4<f32> {
+    var color = color;
+    // Apply the gamma to the straight color rather than the premultiplied one
+    if (r_locals.gamma != 1.0 && color.a > 0.0) {
+        let straight = pow(color.rgb / color.a, vec3<f32>(1.0 / r_locals.gamma));
+        color = vec4<f32>(straight * color.a, color.a);
+    }
+    return vec4<f32>(color.rgb * r_locals.brightness, color.a) * r_locals.ui_tint;
+}
+
+// Narrow or flatten the alpha ramp egui feathers untextured shapes with, around its middle.
+// `slope` is the vertex alpha's change per pixel, which across the ramp is the fill's alpha.
+fn feather(in: VertexOutput, color: vec4<f32>, slope: f32) -> vec4<f32> {
+    if (r_locals.feather == 1.0 || slope <= 0.0 || any(in.tex_coord != vec2<f32>(0.0))) {
+        return color;
+    }
+    let t = min(in.color.a / slope, 1.0);
+    let scaled = clamp((t - 0.5) / r_locals.feather + 0.5, 0.0, 1.0);
+    // Premultiplied, so the color falls off with t too
+    return color * (scaled / max(t, 0.0001));
+}
+
+fn fragment(in: VertexOutput) -> vec4<f32> {
+    // Derivatives before anything that branches
+    let slope = fwidth(in.color.a);
+    let texel = textureSample(r_tex_color, r_tex_sampler, in.tex_coord);
+    return adjust(feather(in, vertex_color(in) * texel, slope));
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color * textureSample(r_tex_color, r_tex_sampler, in.tex_coord);
-}
\ No newline at end of file
+    return fragment(in);
+}
+
+struct CoverageOutput {
+    @location(0) color: vec4<f32>,
+    // Opacity in the red channel of the coverage mask
+    @location(1) coverage: vec4<f32>,
+}
+
+// fs_main that also writes the coverage mask
+@fragment
+fn fs_coverage(in: VertexOutput) -> CoverageOutput {
+    var out: CoverageOutput;
+    out.color = fragment(in);
+    out.coverage = vec4<f32>(out.color.a);
+    return out;
+}
+// Composites an offscreen target that fs_main already adjusted
+@fragment
+fn fs_blit(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, in.tex_coord);
+}
+// Constant low alpha color, additively blended so brightness counts the layers drawn
+@fragment
+fn fs_overdraw(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.6, 0.2, 0.1);
+}
+
+// Glyph coverage at `uv`, filtered linearly across the atlas row as the sampler doesn't magnify
+fn glyph_coverage(uv: vec2<f32>) -> f32 {
+    let size = textureDimensions(r_tex_color, 0);
+    let x = uv.x * f32(size.x) - 0.5;
+    let left = i32(floor(x));
+    let y = clamp(i32(uv.y * f32(size.y)), 0, size.y - 1);
+    let a = textureLoad(r_tex_color, vec2<i32>(clamp(left, 0, size.x - 1), y), 0).a;
+    let b = textureLoad(r_tex_color, vec2<i32>(clamp(left + 1, 0, size.x - 1), y), 0).a;
+    return mix(a, b, x - floor(x));
+}
+
+// Coverage of each color channel's subpixel, spread over its neighbours by a 5 tap filter to
+// keep the color fringes down
+fn subpixel_coverage(in: VertexOutput) -> vec3<f32> {
+    // A third of a pixel to the right in the atlas
+    let step = dpdx(in.tex_coord) / 3.0;
+    let s0 = glyph_coverage(in.tex_coord - step * 3.0);
+    let s1 = glyph_coverage(in.tex_coord - step * 2.0);
+    let s2 = glyph_coverage(in.tex_coord - step);
+    let s3 = glyph_coverage(in.tex_coord);
+    let s4 = glyph_coverage(in.tex_coord + step);
+    let s5 = glyph_coverage(in.tex_coord + step * 2.0);
+    let s6 = glyph_coverage(in.tex_coord + step * 3.0);
+    let left = (s0 + 2.0 * s1 + 3.0 * s2 + 2.0 * s3 + s4) / 9.0;
+    let middle = (s1 + 2.0 * s2 + 3.0 * s3 + 2.0 * s4 + s5) / 9.0;
+    let right = (s2 + 2.0 * s3 + 3.0 * s4 + 2.0 * s5 + s6) / 9.0;
+    if (r_locals.subpixel == 2u) {
+        return vec3<f32>(right, middle, left);
+    }
+    return vec3<f32>(left, middle, right);
+}
+
+// How much of each channel glyphs cover, for darkening the target before fs_subpixel_color
+@fragment
+fn fs_subpixel_mask(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = subpixel_coverage(in);
+    let alpha = adjust(vertex_color(in)).a;
+    return vec4<f32>(coverage * alpha, 0.0);
+}
+
+// Glyph color weighted by each channel's coverage, added onto the darkened target
+@fragment
+fn fs_subpixel_color(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = subpixel_coverage(in);
+    let color = adjust(vertex_color(in));
+    return vec4<f32>(color.rgb * coverage, color.a * (coverage.r + coverage.g + coverage.b) / 3.0);
+}
@@ -0,0 +1,164 @@
+use egui::TextureId;
+use std::fmt;
+
+/// Errors produced by [`crate::EguiPipe`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EguiError {
+    /// A WGSL shader failed to parse or validate. Contains the compiler's report.
+    ShaderCompile(String),
+    /// A texture is larger than the device allows along either side
+    TextureTooLarge {
+        /// The texture egui tried to create
+        id: TextureId,
+        /// Its width and height in pixels
+        size: [u32; 2],
+        /// The device's `max_texture_dimension_2d`
+        max: u32,
+    },
+    /// A texture would be created in a format its 8 bit RGBA pixels can't be uploaded to,
+    /// e.g. one returned by [`crate::EguiPipe::set_texture_format_hook`]
+    UnsupportedFormat {
+        /// The texture egui tried to create
+        id: TextureId,
+        /// The format it would have been created with
+        format: wgpu::TextureFormat,
+    },
+    /// The vertex or index data of a mesh is larger than the device's `max_buffer_size`, so it
+    /// can't be drawn even from buffers of its own. Larger frames are split over several buffers.
+    BufferResize {
+        /// Bytes the buffer would need
+        needed: u64,
+        /// The device's `max_buffer_size`
+        max: u64,
+    },
+    /// Part of a texture was updated before the texture was created
+    UnknownTexture(TextureId),
+    /// A [`crate::ScreenDescriptor`] can't be rendered with. Contains what is wrong with it.
+    InvalidScreen(String),
+    /// The device lacks features something needs. Contains the missing ones.
+    MissingFeatures(wgpu::Features),
+    /// A frame has more vertices than a 32 bit index can address, so the meshes past the limit
+    /// are skipped
+    TooManyVertices {
+        /// Vertices the frame would need
+        count: usize,
+    },
+    /// A render target doesn't match what the pipeline was built for
+    TargetMismatch {
+        /// Format and sample count of the pipeline's target
+        expected: (wgpu::TextureFormat, u32),
+        /// Format and sample count of the target given
+        got: (wgpu::TextureFormat, u32),
+    },
+    /// A texture can't be registered under an id, as another texture already has it
+    TextureIdTaken(TextureId),
+    /// A pipeline layout lacks a bind group the pipe binds, or has it with other bindings, e.g. a
+    /// pipeline given to `Painter::custom_pipeline`. The pipe is built with its own layout instead.
+    MissingBindGroupLayout {
+        /// Set index of the bind group
+        index: usize,
+        /// What the pipe binds there
+        purpose: &'static str,
+    },
+    /// A paint callback wasn't made with [`crate::paint_callback`], so the pipe can't draw it
+    CallbackMismatch,
+}
+
+impl fmt::Display for EguiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EguiError::ShaderCompile(report) => {
+                write!(f, "egui shader failed to compile: {}", report)
+            }
+            EguiError::TextureTooLarge { id, size, max } => write!(
+                f,
+                "egui texture {:?} is {}x{}, larger than the device's limit of {}",
+                id, size[0], size[1], max
+            ),
+            EguiError::UnsupportedFormat { id, format } => write!(
+                f,
+                "egui texture {:?} can't be uploaded as {:?}, it needs an 8 bit RGBA or BGRA format",
+                id, format
+            ),
+            EguiError::BufferResize { needed, max } => write!(
+                f,
+                "egui needs a {} byte buffer, larger than the device's limit of {}",
+                needed, max
+            ),
+            EguiError::InvalidScreen(reason) => {
+                write!(f, "egui screen descriptor is invalid: {}", reason)
+            }
+            EguiError::MissingFeatures(features) => {
+                write!(f, "egui needs the device features {:?}", features)
+            }
+            EguiError::UnknownTexture(id) => {
+                write!(f, "egui updated part of texture {:?}, which doesn't exist", id)
+            }
+            EguiError::TooManyVertices { count } => write!(
+                f,
+                "egui frame needs {} vertices, more than a u32 index can address",
+                count
+            ),
+            EguiError::TargetMismatch { expected, got } => write!(
+                f,
+                "egui pipeline renders to {:?} with {} samples, the target is {:?} with {}",
+                expected.0, expected.1, got.0, got.1
+            ),
+            EguiError::TextureIdTaken(id) => {
+                write!(f, "egui texture id {:?} is already taken", id)
+            }
+            EguiError::MissingBindGroupLayout { index, purpose } => write!(
+                f,
+                "egui pipeline layout doesn't have the {} bind group at set {}",
+                purpose, index
+            ),
+            EguiError::CallbackMismatch => write!(
+                f,
+                "egui paint callback wasn't made with egui_pigeon::paint_callback"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EguiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_describes_the_error() {
+        let id = TextureId::Managed(3);
+        let cases = [
+            (
+                EguiError::TextureTooLarge {
+                    id,
+                    size: [9000, 20],
+                    max: 8192,
+                },
+                "egui texture Managed(3) is 9000x20, larger than the device's limit of 8192",
+            ),
+            (
+                EguiError::UnsupportedFormat {
+                    id,
+                    format: wgpu::TextureFormat::R8Unorm,
+                },
+                "egui texture Managed(3) can't be uploaded as R8Unorm, it needs an 8 bit RGBA or BGRA format",
+            ),
+            (
+                EguiError::BufferResize {
+                    needed: 512,
+                    max: 256,
+                },
+                "egui needs a 512 byte buffer, larger than the device's limit of 256",
+            ),
+            (
+                EguiError::UnknownTexture(TextureId::User(1)),
+                "egui updated part of texture User(1), which doesn't exist",
+            ),
+        ];
+        for (error, message) in cases {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+}
@@ -4,17 +4,139 @@ use egui::{
     epaint::{ImageDelta, Vertex},
     ImageData, TextureId,
 };
-use euclid::{Point2D, Rect, Size2D};
+use euclid::{Point2D, Rect, Size2D, Transform3D};
 use parrot::{
     binding::{Binding, BindingGroup, BindingType},
+    buffers::index::{IndexBuffer, IndexBuffer32},
+    painter::PassOp,
+    painter::RenderBundleExtention,
     pipeline::{Blending, PipelineCore, PipelineDescription, Set},
     transform::*,
     vertex::VertexFormat,
-    buffers::index::IndexBuffer32, Painter, Plumber, RenderPassExtention, Rgba8, Sampler, Texture, UniformBuffer,
+    Painter, Plumber, RenderPassExtention, Rgba, Rgba8, Sampler, Texture, UniformBuffer,
     VertexBuffer,
 };
 use pigeon::pigeon::Container;
-use std::{collections::HashMap, ops::Deref};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+#[cfg(feature = "blur")]
+pub mod blur;
+mod callback;
+pub mod cursor;
+mod error;
+pub mod panel;
+pub mod selftest;
+mod timer;
+#[cfg(feature = "egui_winit")]
+pub mod winit_frame;
+
+use callback::CallbackSlot;
+pub use callback::{
+    paint_callback, CallbackFn, CallbackResources, ContainerCallback, ContainerPipes, EguiCallback,
+};
+use cursor::{CursorSheet, CURSOR_TEXTURE_ID};
+pub use error::EguiError;
+pub use selftest::{selftest, SelfTestReport};
+use timer::GpuTimer;
+#[cfg(feature = "egui_winit")]
+pub use winit_frame::EguiWinit;
+
+const SHADER: &str = include_str!("./egui.wgsl");
+
+/// Bind group of the uniform in the pipeline layout
+const UNIFORM_SET: usize = 0;
+/// Bind group of the texture and its sampler in the pipeline layout
+const TEXTURE_SET: usize = 1;
+
+// A `profiling` scope lasting to the end of the block, nothing without the profiling feature
+macro_rules! scope {
+    ($name:literal) => {
+        #[cfg(feature = "profiling")]
+        profiling::scope!($name);
+    };
+}
+
+/// Blend state for egui's premultiplied alpha colors
+const PREMULTIPLIED_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// Format of the coverage mask target, see [`EguiPipeBuilder::coverage_mask`]
+pub const COVERAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+/// Texture id egui manages its font atlas under, the same as `TextureId::default()`. Shapes sample
+/// its white texel, so the features for text and solid shapes look for this id.
+pub const FONT_ATLAS_ID: TextureId = TextureId::Managed(0);
+
+/// Blend state accumulating the UI's opacity into the coverage mask
+const COVERAGE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::OneMinusSrc,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::OVER,
+};
+
+/// Blend state darkening the target by the per channel coverage of fs_subpixel_mask
+const SUBPIXEL_MASK_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::OneMinusSrc,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// Blend state adding the color of fs_subpixel_color onto the darkened target
+const SUBPIXEL_COLOR_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// Blend state adding every fragment onto the target
+const ADDITIVE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::SrcAlpha,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
 
 pub const VERTEX_LAYOUT: [VertexFormat; 3] = [
     VertexFormat::Floatx2,
@@ -22,37 +144,398 @@ pub const VERTEX_LAYOUT: [VertexFormat; 3] = [
     VertexFormat::Uint32,
 ];
 
-#[allow(dead_code)]
-pub struct CallbackFn {
-    add: Box<AddCallback>,
-    draw: Box<PaintCallback>,
+/// Color space of the render target, which decides how egui's sRGB colors are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// The target has an sRGB format. The shader outputs linear colors and the GPU encodes them,
+    /// blending in linear space.
+    #[default]
+    SrgbTarget,
+    /// The target has a linear (`Unorm`) format. Colors and textures stay in gamma space and are
+    /// written as is, which is how egui's other non-sRGB backends blend.
+    LinearTarget,
 }
-type AddCallback = dyn Fn(&mut Container) + Send + Sync;
-type PaintCallback = dyn Fn(&mut Container) + Send + Sync;
 
-impl Default for CallbackFn {
-    fn default() -> Self {
+impl ColorSpace {
+    /// Format of the render target the pipeline is built for
+    pub fn target_format(&self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::SrgbTarget => wgpu::TextureFormat::Bgra8UnormSrgb,
+            ColorSpace::LinearTarget => wgpu::TextureFormat::Bgra8Unorm,
+        }
+    }
+
+    /// Format egui textures are created with
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::SrgbTarget => wgpu::TextureFormat::Bgra8UnormSrgb,
+            ColorSpace::LinearTarget => wgpu::TextureFormat::Bgra8Unorm,
+        }
+    }
+}
+
+/// Debug visualisations [`EguiPipe`] can render instead of the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugMode {
+    /// Render the UI normally
+    #[default]
+    Off,
+    /// Draw every triangle as a faint additive color, so brighter areas are drawn over more often
+    Overdraw,
+    /// Render the UI with a 1 pixel outline around every distinct clip rect, colored by the texture
+    /// of the first group using it. Takes effect from the next `prepare`.
+    ClipRects,
+    /// Multiply every mesh by a color picked from its texture id, from a palette that stays
+    /// distinct with common color blindness, to show which meshes share a texture. Takes effect
+    /// from the next `prepare`.
+    TextureTint,
+}
+
+/// How [`EguiPipe`] rounds the edges of clip rects to whole pixels, see
+/// [`EguiPipe::set_clip_rounding`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipRounding {
+    /// Round every edge to the nearest pixel
+    #[default]
+    Round,
+    /// Round every edge down
+    Floor,
+    /// Round the top left down and the bottom right up, so content is never clipped. Panels
+    /// sharing an edge overlap by up to a pixel instead of leaving a gap.
+    Expand,
+}
+
+/// What [`EguiPipe`] does with a clip rect that has a NaN edge, e.g. from a widget animating with
+/// a zero duration, see [`EguiPipe::set_nan_clip`]. Infinite edges are fine, they clip at the
+/// edge of the target like egui's `Rect::EVERYTHING`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NanClip {
+    /// Draw the primitive clipped to the whole target
+    #[default]
+    FullScreen,
+    /// Leave the primitive out of the frame
+    Skip,
+}
+
+/// When [`EguiPipe`] reallocates its vertex and index buffers smaller, see
+/// [`EguiPipe::set_shrink_policy`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ShrinkPolicy {
+    /// Keep the buffers at the largest size needed so far
+    #[default]
+    Never,
+    /// Reallocate both buffers to fit once every frame for `frames` frames in a row has used less
+    /// than `fraction` of both, e.g. 0.25 for 120 frames
+    WhenUnderused {
+        /// Fraction of the capacity a frame has to stay below
+        fraction: f32,
+        /// Frames in a row that have to stay below it
+        frames: u32,
+    },
+}
+
+/// Places the UI in a world instead of over the screen, see [`EguiPipe::set_transform`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldTransform {
+    /// Takes the UI's points, y down from its top left corner, into the world
+    pub model: Transform3D<f32, ScreenSpace, WorldSpace>,
+    /// Takes the world to clip space, e.g. the camera's projection
+    pub projection: Transform3D<f32, WorldSpace, ScreenSpace>,
+}
+
+impl WorldTransform {
+    /// Place the UI with `model` in a pigeon world drawn on a `screen` sized target, projected the
+    /// way pigeon projects its own pipelines: the origin in the middle, y up, one unit per pixel
+    pub fn pigeon(
+        model: Transform3D<f32, ScreenSpace, WorldSpace>,
+        screen: Size2D<f32, ScreenSpace>,
+    ) -> Self {
+        let (w, h) = (screen.width / 2.0, screen.height / 2.0);
+        let ortho = Transform3D::ortho(-w, w, -h, h, 50.0, -50.0);
         Self {
-            add: Box::new(|_| ()),
-            draw: Box::new(|_| ()),
+            model,
+            projection: pigeon::pigeon::OPENGL_TO_WGPU_MATRIX.then(&ortho),
+        }
+    }
+}
+
+/// Order of the color subpixels across an LCD screen, see [`EguiPipe::set_subpixel_text`]
+#[cfg(feature = "subpixel")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubpixelOrder {
+    /// Red on the left, the most common layout
+    #[default]
+    Rgb,
+    /// Blue on the left
+    Bgr,
+}
+
+/// Clockwise rotation of the UI on its render target, e.g. for a display mounted on its side, see
+/// [`EguiPipe::set_rotation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreenRotation {
+    /// Drawn upright
+    #[default]
+    None,
+    /// The top of the UI is drawn along the right of the target
+    Deg90,
+    /// Drawn upside down
+    Deg180,
+    /// The top of the UI is drawn along the left of the target
+    Deg270,
+}
+
+impl ScreenRotation {
+    /// The rotation undoing this one
+    pub fn inverse(self) -> Self {
+        match self {
+            ScreenRotation::None => ScreenRotation::None,
+            ScreenRotation::Deg90 => ScreenRotation::Deg270,
+            ScreenRotation::Deg180 => ScreenRotation::Deg180,
+            ScreenRotation::Deg270 => ScreenRotation::Deg90,
+        }
+    }
+
+    /// Size of the target a UI of `size` is drawn on, with width and height swapped for quarter turns
+    pub fn rotate_size<T: Copy>(self, size: [T; 2]) -> [T; 2] {
+        match self {
+            ScreenRotation::None | ScreenRotation::Deg180 => size,
+            ScreenRotation::Deg90 | ScreenRotation::Deg270 => [size[1], size[0]],
+        }
+    }
+
+    /// Where `rect`, in the pixels of a UI of `size`, ends up on the rotated target
+    pub fn rotate_rect(
+        self,
+        rect: Rect<f32, ScreenSpace>,
+        size: [f32; 2],
+    ) -> Rect<f32, ScreenSpace> {
+        let [width, height] = size;
+        let min = match self {
+            ScreenRotation::None => rect.origin,
+            ScreenRotation::Deg90 => Point2D::new(height - rect.max_y(), rect.min_x()),
+            ScreenRotation::Deg180 => Point2D::new(width - rect.max_x(), height - rect.max_y()),
+            ScreenRotation::Deg270 => Point2D::new(rect.min_y(), width - rect.max_x()),
+        };
+        let [w, h] = self.rotate_size([rect.width(), rect.height()]);
+        Rect::new(min, Size2D::new(w, h))
+    }
+
+    // Maps clip space positions, as columns of a WGSL mat2x2
+    fn matrix(self) -> [[f32; 2]; 2] {
+        match self {
+            ScreenRotation::None => [[1.0, 0.0], [0.0, 1.0]],
+            ScreenRotation::Deg90 => [[0.0, -1.0], [1.0, 0.0]],
+            ScreenRotation::Deg180 => [[-1.0, 0.0], [0.0, -1.0]],
+            ScreenRotation::Deg270 => [[0.0, 1.0], [-1.0, 0.0]],
         }
     }
+
+    // rotate_rect for whole pixel rects
+    fn rotate_pixel_rect(
+        self,
+        rect: Rect<u32, ScreenSpace>,
+        size: [u32; 2],
+    ) -> Rect<u32, ScreenSpace> {
+        self.rotate_rect(rect.to_f32(), [size[0] as f32, size[1] as f32])
+            .round()
+            .to_u32()
+    }
+}
+
+/// Stencil test limiting where [`EguiPipe`] draws, see [`EguiPipe::set_stencil_mask`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilMask {
+    /// Format of the render pass' depth stencil attachment
+    pub format: wgpu::TextureFormat,
+    /// The UI is drawn where `reference <compare> stencil value` holds
+    pub compare: wgpu::CompareFunction,
+    /// Stencil reference the attachment is compared against
+    pub reference: u32,
+}
+
+/// Depth buffer used by [`EguiPipe::render_opaque`], see [`EguiPipe::set_depth_prepass`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthPrepass {
+    /// Format of the render pass' depth attachment
+    pub format: wgpu::TextureFormat,
+    /// The UI is drawn at depths from 0 up to this, later groups nearer. Draw the scene behind it at
+    /// greater depths for hidden pixels to be rejected.
+    pub max_depth: f32,
 }
 
 /// Information about the screen used for rendering.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ScreenDescriptor {
     /// Size of the window in physical pixels.
     pub size_in_pixels: [u32; 2],
 
     /// HiDPI scale factor (pixels per point).
     pub pixels_per_point: f32,
+
+    /// Size of the render target relative to the window, e.g. 2.0 to supersample. Layout still uses
+    /// `pixels_per_point`. Render with [`EguiPipe::render_scaled`] when this isn't 1.0.
+    pub render_scale: f32,
+
+    /// Separate X and Y pixels per point for stretched targets. Both are `pixels_per_point` when `None`.
+    pub scale_factors: Option<[f32; 2]>,
+
+    /// Units the clip rects of the primitives are in
+    pub clip_space: ClipSpace,
+}
+
+/// Units of the clip rects passed to [`EguiPipe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipSpace {
+    /// Points, as egui tessellates them. Scaled by the [`ScreenDescriptor`]'s scale factors.
+    #[default]
+    Logical,
+    /// Physical pixels of the window, e.g. from custom tessellation
+    Physical,
 }
 
 impl ScreenDescriptor {
+    /// Descriptor for a window of `size_in_pixels` with egui's clip rects in points, rendered at
+    /// its own size
+    pub fn new(size_in_pixels: [u32; 2], pixels_per_point: f32) -> Self {
+        Self {
+            size_in_pixels,
+            pixels_per_point,
+            render_scale: 1.0,
+            scale_factors: None,
+            clip_space: ClipSpace::Logical,
+        }
+    }
+
+    /// Descriptor for the inside of `window` at its current scale factor
+    #[cfg(feature = "winit")]
+    pub fn from_window(window: &winit::window::Window) -> Self {
+        let size = window.inner_size();
+        Self::new([size.width, size.height], window.scale_factor() as f32)
+    }
+
+    /// The screen in points, e.g. for [`egui::RawInput::screen_rect`]
+    pub fn screen_rect(&self) -> egui::Rect {
+        let [width, height] = self.screen_size_in_points();
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width, height))
+    }
+
+    /// The window in physical pixels
+    pub fn physical_rect(&self) -> egui::Rect {
+        let [width, height] = self.size_in_pixels;
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32))
+    }
+
+    /// Convert a length in points to physical pixels
+    pub fn points_to_pixels(&self, points: f32) -> f32 {
+        points * self.pixels_per_point
+    }
+
+    /// The descriptor with its pixels per point, and both `scale_factors`, multiplied by `ui_scale`.
+    /// Clip rects in [`ClipSpace::Physical`] aren't scaled.
+    pub fn with_ui_scale(self, ui_scale: f32) -> Self {
+        Self {
+            pixels_per_point: self.pixels_per_point * ui_scale,
+            scale_factors: self
+                .scale_factors
+                .map(|[x, y]| [x * ui_scale, y * ui_scale]),
+            ..self
+        }
+    }
+
+    /// Check the descriptor can be rendered with: the scales are positive and finite and the render
+    /// target is neither empty nor larger than `max_size` (the device's `max_texture_dimension_2d`)
+    /// on either side. `prepare` skips drawing frames that fail this, quietly for a
+    /// [minimized](ScreenDescriptor::is_minimized) window.
+    pub fn validate(&self, max_size: u32) -> Result<(), EguiError> {
+        let invalid = |reason: String| Err(EguiError::InvalidScreen(reason));
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        if !positive(self.pixels_per_point) {
+            return invalid(format!("pixels_per_point is {}", self.pixels_per_point));
+        }
+        if !self.scale_factors().into_iter().all(positive) {
+            return invalid(format!("scale_factors are {:?}", self.scale_factors()));
+        }
+        if !positive(self.render_scale) {
+            return invalid(format!("render_scale is {}", self.render_scale));
+        }
+        let [width, height] = self.render_size_in_pixels();
+        if width == 0 || height == 0 {
+            return invalid(format!("the render target is {}x{}", width, height));
+        }
+        if width > max_size || height > max_size {
+            return invalid(format!(
+                "the render target is {}x{}, larger than the limit of {}",
+                width, height, max_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the window has no area, as when it's minimized on Windows. `prepare` draws nothing
+    /// but still uploads textures for these frames, and picks up again when the size comes back;
+    /// skip acquiring the surface too, its attachments can't be zero sized.
+    pub fn is_minimized(&self) -> bool {
+        self.size_in_pixels.contains(&0)
+    }
+
+    /// Size of the render target in physical pixels, `size_in_pixels` scaled by `render_scale`
+    pub fn render_size_in_pixels(&self) -> [u32; 2] {
+        [
+            (self.size_in_pixels[0] as f32 * self.render_scale).round() as u32,
+            (self.size_in_pixels[1] as f32 * self.render_scale).round() as u32,
+        ]
+    }
+
+    /// Pixels per point along X and Y
+    pub fn scale_factors(&self) -> [f32; 2] {
+        self.scale_factors
+            .unwrap_or([self.pixels_per_point, self.pixels_per_point])
+    }
+
+    // Scale taking clip rects to window pixels
+    fn clip_scale(&self) -> [f32; 2] {
+        match self.clip_space {
+            ClipSpace::Logical => self.scale_factors(),
+            ClipSpace::Physical => [1.0, 1.0],
+        }
+    }
+
+    // Scale taking clip rects to the render target pixels scissors are in
+    fn scissor_scale(&self) -> [f32; 2] {
+        let [x, y] = self.clip_scale();
+        [x * self.render_scale, y * self.render_scale]
+    }
+
+    // Scissor of `clip_rect`, zoomed and displaced to `moved`, in render target pixels. One covering
+    // the window covers all of a supersampled target, which rounding the scaled clip rect can leave
+    // a pixel short of.
+    fn scissor_rect(
+        &self,
+        clip_rect: &egui::Rect,
+        moved: &egui::Rect,
+        rounding: ClipRounding,
+    ) -> Rect<u32, ScreenSpace> {
+        let size = self.render_size_in_pixels();
+        if clip_rect.expand(0.5).contains_rect(self.window_clip_rect()) {
+            return Rect::from_size(Size2D::from(size));
+        }
+        calculate_pixel_rect(moved, self.scissor_scale(), size, rounding)
+    }
+
+    // The window in the units of the clip rects
+    fn window_clip_rect(&self) -> egui::Rect {
+        match self.clip_space {
+            ClipSpace::Logical => self.screen_rect(),
+            ClipSpace::Physical => self.physical_rect(),
+        }
+    }
+
     fn screen_size_in_points(&self) -> [f32; 2] {
+        let scale = self.scale_factors();
         [
-            self.size_in_pixels[0] as f32 / self.pixels_per_point,
-            self.size_in_pixels[1] as f32 / self.pixels_per_point,
+            self.size_in_pixels[0] as f32 / scale[0],
+            self.size_in_pixels[1] as f32 / scale[1],
         ]
     }
 }
@@ -63,34 +546,483 @@ pub struct Group {
     range: std::ops::Range<u32>,
     tex_id: TextureId,
     pixel_rect: Rect<u32, ScreenSpace>,
+    bounds: egui::Rect,
+    opaque: bool,
+    /// Whether the group is glyphs drawn with subpixel coverage
+    text: bool,
+    /// Buffers the group is drawn from, see [`Group::chunk`]
+    chunk: usize,
+    /// See [`Group::primitive`]
+    primitive: Option<usize>,
+}
+
+impl Group {
+    /// Range of indices in the index buffer drawn by this group, that of its [`Group::chunk`]
+    pub fn range(&self) -> std::ops::Range<u32> {
+        self.range.clone()
+    }
+
+    /// Which buffers the group is drawn from: 0 for [`EguiPipe::vertex_buffer`] and
+    /// [`EguiPipe::index_buffer`], and higher for the extra ones of a frame too large for a single
+    /// buffer of the device
+    pub fn chunk(&self) -> usize {
+        self.chunk
+    }
+
+    /// Index of the clipped primitive the group was cut from, in the order given to `prepare`.
+    /// None for groups the pipe adds itself, the [`EguiPipe::set_background`] and the cursor.
+    pub fn primitive(&self) -> Option<usize> {
+        self.primitive
+    }
+
+    /// Texture the group is drawn with
+    pub fn tex_id(&self) -> TextureId {
+        self.tex_id
+    }
+
+    /// Scissor rect the group is clipped to, in physical pixels
+    pub fn pixel_rect(&self) -> Rect<u32, ScreenSpace> {
+        self.pixel_rect
+    }
+
+    /// Bounding box of the vertices of the mesh the group was cut from, in points. Often much
+    /// smaller than the clip rect, e.g. a label in a full window panel, so a group can be culled
+    /// when it is outside the area being redrawn.
+    pub fn bounds(&self) -> egui::Rect {
+        self.bounds
+    }
+
+    /// Whether the group is drawn by [`EguiPipe::render_opaque`] rather than `render`
+    pub fn opaque(&self) -> bool {
+        self.opaque
+    }
+}
+
+/// Snapshot of one draw the next render will record, see [`EguiPipe::draw_list`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawInfo {
+    /// Number of indices drawn
+    pub index_count: u32,
+    /// Texture drawn with
+    pub tex_id: TextureId,
+    /// Scissor rect, in physical pixels
+    pub pixel_rect: Rect<u32, ScreenSpace>,
+    /// Whether it is drawn front to back by the depth prepass, see [`EguiPipe::set_depth_prepass`]
+    pub opaque: bool,
+}
+
+/// Index buffer of either width, see [`EguiPipeBuilder::index_format`]
+#[derive(Debug)]
+pub enum EguiIndexBuffer {
+    /// 16 bit indices
+    U16(IndexBuffer),
+    /// 32 bit indices
+    U32(IndexBuffer32),
+}
+
+impl EguiIndexBuffer {
+    /// Format to bind the buffer with
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            Self::U16(_) => wgpu::IndexFormat::Uint16,
+            Self::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    /// The whole buffer
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        match self {
+            Self::U16(b) => b.slice(),
+            Self::U32(b) => b.slice(),
+        }
+    }
 }
 
+/// Filters and wrap modes of a sampler, as (mag, min, wrap u, wrap v)
+type SamplerKey = (
+    wgpu::FilterMode,
+    wgpu::FilterMode,
+    wgpu::AddressMode,
+    wgpu::AddressMode,
+);
+
+/// Pipelines darkening the target by the glyphs' coverage, then adding their color
+type SubpixelPipelines = (wgpu::RenderPipeline, wgpu::RenderPipeline);
+
+/// A render bundle and the scissor rect it is replayed with
+type Bundle = (Rect<u32, ScreenSpace>, wgpu::RenderBundle);
+/// Groups sharing a scissor rect and their texture bindings
+type BundleRun<'a> = (Rect<u32, ScreenSpace>, Vec<(&'a Group, &'a BindingGroup)>);
+
 /// Pipeline for egui
 #[derive(Debug)]
 pub struct EguiPipe {
     /// Vertex buffer
     pub vertex_buffer: VertexBuffer,
     /// Index bufer
-    pub index_buffer: IndexBuffer32,
-    /// Egui textures
-    pub egui_texture: HashMap<egui::TextureId, (Texture, BindingGroup)>,
+    pub index_buffer: EguiIndexBuffer,
+    /// Preferred index width, see [`EguiPipeBuilder::index_format`]
+    index_format: wgpu::IndexFormat,
+    /// Egui textures. The textures are shared with other pipes through [`TextureStore`].
+    pub egui_texture: HashMap<egui::TextureId, (Arc<Texture>, BindingGroup)>,
+    /// Store the textures are shared through and the generation last synced from it
+    texture_store: Option<(SharedTextureStore, u64)>,
     /// Groups
     pub groups: Vec<Group>,
-    /// Sampler used by egui textures
+    /// Sampler used by egui textures. Call [`EguiPipe::rebuild_texture_bindings`] after replacing it.
     pub sampler: Sampler,
-    /// Container to hold shapes to be drawn with paint callback
+    /// Samplers for textures that don't clamp to the edge, created on first use
+    samplers: HashMap<SamplerKey, Sampler>,
+    /// Wrap modes of textures set with [`EguiPipe::set_texture_wrap`]
+    texture_wrap: HashMap<TextureId, [wgpu::AddressMode; 2]>,
+    /// Never drawn. Shapes are drawn inside the UI with [`ContainerCallback`] instead.
+    #[deprecated(note = "never drawn, add the shapes with a ContainerCallback")]
     pub container: Option<Container>,
+    /// Paint callbacks from the last prepare, in draw order
+    callbacks: Vec<PreparedCallback>,
+    /// Buffers of the geometry past the first chunk, when the frame is larger than a buffer can be
+    chunks: Vec<(VertexBuffer, IndexBuffer32)>,
+    /// Resources shared by the paint callbacks, see [`EguiPipe::callback_resources`]
+    callback_resources: CallbackResources,
+    /// Color space of the render target
+    pub color_space: ColorSpace,
+    /// Samples per pixel of the target the pipeline was built for, see [`EguiPipe::sample_count`]
+    sample_count: u32,
+    /// Debug visualisation to render
+    debug_mode: DebugMode,
+    /// How clip rects are rounded to pixels
+    clip_rounding: ClipRounding,
+    /// What's done with NaN clip rects and whether one was logged, see [`EguiPipe::set_nan_clip`]
+    nan_clip: NanClip,
+    warned_nan_clip: bool,
+    /// Only upload the uniform when invalidated, see [`EguiPipeBuilder::static_screen`]
+    static_screen: bool,
+    /// Whether the uniform needs uploading in the next prepare
+    uniform_dirty: bool,
+    /// Uniform built by the last prepare
+    uniform: Uniform,
+    /// Settings the uniform is built from
+    shader_settings: ShaderSettings,
+    /// Multiplies the whole UI, see [`EguiPipe::set_tint`]
+    tint: egui::Rgba,
+    /// Whether shapes are snapped to pixels, see [`EguiPipe::set_pixel_snapping`]
+    pixel_snapping: bool,
+    /// Width of shape edges' antialiasing, see [`EguiPipe::set_feather`]
+    feather: f32,
+    /// How the UI is turned on the target, see [`EguiPipe::set_rotation`]
+    rotation: ScreenRotation,
+    /// Where the UI is placed in a world, see [`EguiPipe::set_transform`]
+    transform: Option<WorldTransform>,
+    /// Whether the UI is drawn upside down, see [`EguiPipe::set_flip_y`]
+    flip_y: bool,
+    /// Points the UI is moved by, see [`EguiPipe::set_displacement`]
+    displacement: egui::Vec2,
+    /// Color drawn under the UI, see [`EguiPipe::set_background`]
+    background: Option<egui::Color32>,
+    /// Whether scissors move with the UI, see [`EguiPipe::set_displace_scissors`]
+    displace_scissors: bool,
+    /// Part of the target the UI is drawn into, see [`EguiPipe::set_destination`]
+    destination: Option<Rect<u32, ScreenSpace>>,
+    /// Time the shader gets instead of the clock, see [`EguiPipe::set_time`]
+    time: Option<f32>,
+    /// When the pipe was created, for the shader's clock
+    created: Instant,
+    /// Subpixel order glyphs are drawn with, see [`EguiPipe::set_subpixel_text`]
+    #[cfg(feature = "subpixel")]
+    subpixel_text: Option<SubpixelOrder>,
+    /// Sample count and the mask and color pipelines for subpixel text
+    subpixel_pipelines: Option<(u32, SubpixelPipelines)>,
+    /// Usages added to every egui texture, see [`EguiPipeBuilder::extra_texture_usages`]
+    extra_texture_usages: wgpu::TextureUsages,
+    /// Overrides the format of new egui textures, see [`EguiPipe::set_texture_format_hook`]
+    texture_format_hook: FormatHook,
+    /// Whether texture changes are uploaded in prepare
+    uploads_enabled: bool,
+    /// Texture changes queued while uploads are paused, in order
+    pending_textures: PendingTextures,
+    /// Textures egui freed last frame, removed in the next prepare once that frame has rendered
+    pending_free: Vec<TextureId>,
+    /// Textures kept when egui frees them, see [`EguiPipe::pin_texture`]
+    pinned_textures: HashSet<TextureId>,
+    /// Most textures kept before evicting, see [`EguiPipe::set_max_textures`]
+    max_textures: Option<usize>,
+    /// Bind groups of textures made outside the pipe and the next id to give one, see
+    /// [`EguiPipe::register_wgpu_texture`]
+    wgpu_textures: HashMap<TextureId, BindingGroup>,
+    next_wgpu_texture: u64,
+    /// CPU copies of the textures if kept, see [`EguiPipe::set_retain_textures`]
+    retained_textures: Option<RetainedTextures>,
+    /// Number of the last prepare, and the last prepare each texture was drawn in, stamped by
+    /// `render`, which only has `&self`
+    texture_frame: u64,
+    texture_last_used: Mutex<HashMap<TextureId, u64>>,
+    /// Scale on top of the descriptor's pixels per point, see [`EguiPipe::set_ui_scale`]
+    ui_scale: f32,
+    /// Render only scale around the middle of the screen, see [`EguiPipe::set_ui_zoom`]
+    ui_zoom: f32,
+    /// Scale on top of the descriptor's render scale, see [`EguiPipe::set_render_scale`]
+    render_scale: f32,
+    /// Whether to replay the draws from render bundles, see [`EguiPipe::set_render_bundles`]
+    render_bundles: bool,
+    /// When the buffers are reallocated smaller, see [`EguiPipe::set_shrink_policy`]
+    shrink_policy: ShrinkPolicy,
+    /// Frames in a row the buffers have been underused for
+    underused_frames: u32,
+    /// Recorded bundles, one per run of groups sharing a scissor rect, and their stats
+    bundles: Option<(Vec<Bundle>, RenderStats)>,
+    /// Hash of what the bundles draw, to tell when they need recording again
+    draw_hash: u64,
+    /// Vertices uploaded by the last prepare, to tell when only their colors change
+    prev_vertices: Vec<Vertex>,
+    /// Bytes of vertex data the last prepare uploaded
+    vertex_upload_bytes: usize,
+    /// Counters from the last render. Behind a lock as rendering only borrows the pipe
+    render_stats: Mutex<RenderStats>,
+    /// Draw calls a render may issue before warning, see [`EguiPipe::set_draw_call_budget`]
+    draw_call_budget: Option<u32>,
+    /// Whether the last render went over the budget, so it is only logged once
+    over_budget: AtomicBool,
+    /// Timestamp queries around the draws when the device supports them
+    gpu_timer: Option<GpuTimer>,
+    /// Whether to label the render pass for GPU captures
+    debug_labels: bool,
+    /// Whether render writes the coverage mask, see [`EguiPipeBuilder::coverage_mask`]
+    coverage_mask: bool,
+    /// Whether the UI pipelines rasterize conservatively, see
+    /// [`EguiPipeBuilder::conservative_rasterization`]
+    conservative_rasterization: bool,
+    /// Texture drawn in place of textures that haven't been uploaded
+    fallback_texture: Option<TextureId>,
+    /// Blurred backdrop bound in place of [`blur::BLUR_TEXTURE_ID`], see [`EguiPipe::set_blur_backdrop`]
+    #[cfg(feature = "blur")]
+    blur_backdrop: Option<BindingGroup>,
+    /// Sprites of the software cursor when it is enabled
+    software_cursor: Option<CursorSheet>,
+    /// Whether the cursor sheet needs uploading or removing in the next prepare
+    cursor_dirty: bool,
+    /// Pointer position and icon for the software cursor
+    cursor: Option<(egui::Pos2, egui::CursorIcon)>,
     /// core
     pub core: PipelineCore,
+    /// Shader module used to build pipeline variants
+    shader: ShaderModule,
+    /// Pipeline for [`EguiPipe::render_layer`] and the format it was built for
+    layer_pipeline: Option<(wgpu::TextureFormat, wgpu::RenderPipeline)>,
+    /// Pipeline for [`DebugMode::Overdraw`], created on first use
+    overdraw_pipeline: Option<wgpu::RenderPipeline>,
+    /// Stencil test applied by render, if any
+    stencil_mask: Option<StencilMask>,
+    /// Depth buffer opaque groups are drawn into first, if any
+    depth_prepass: Option<DepthPrepass>,
+    /// Pipelines for the opaque and blended groups with the depth prepass and their depth format
+    prepass_pipelines: Option<(
+        wgpu::TextureFormat,
+        wgpu::RenderPipeline,
+        wgpu::RenderPipeline,
+    )>,
+    /// Pipeline testing the stencil and the format and compare function it was built for
+    stencil_pipeline: Option<(
+        wgpu::TextureFormat,
+        wgpu::CompareFunction,
+        wgpu::RenderPipeline,
+    )>,
+    /// Indices of the clip rect outlines appended by prepare in [`DebugMode::ClipRects`]
+    clip_rects_range: Option<std::ops::Range<u32>>,
+    /// Last mismatched pixels_per_point warned about, so it is only logged once
+    warned_pixels_per_point: Option<[f32; 2]>,
+    /// Last invalid screen descriptor error logged, so it is only logged once
+    warned_screen: Option<EguiError>,
+    /// Recoverable errors since the last [`EguiPipe::take_errors`]
+    errors: Vec<EguiError>,
+    /// Size of the render target from the last prepare
+    render_size: Size2D<u32, ScreenSpace>,
+    /// Offscreen target for [`EguiPipe::render_scaled`] and [`EguiPipe::render_composited`] and
+    /// its binding group
+    scaled_target: Option<(Texture, BindingGroup)>,
+    /// Pipeline compositing offscreen targets, created on first use
+    blit_pipeline: Option<wgpu::RenderPipeline>,
+    /// Pipeline for [`EguiPipe::render_composited`] and the sample count it was built for
+    composite_pipeline: Option<(u32, wgpu::RenderPipeline)>,
+    /// Linear sampler for compositing offscreen targets
+    blit_sampler: Sampler,
+}
+
+/// Commands [`EguiPipe`] recorded in the renders of a frame, see [`EguiPipe::last_render_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    /// Indexed draw calls issued
+    pub draw_calls: u32,
+    /// Scissor rects set
+    pub scissor_changes: u32,
+    /// Texture bind groups set
+    pub binding_changes: u32,
+    /// Groups not drawn because their clip rect was empty or their texture missing
+    pub skipped_groups: u32,
+}
+
+/// A paint callback and where it's drawn
+struct PreparedCallback {
+    /// Number of groups drawn before it
+    before_group: usize,
+    info: egui::epaint::PaintCallbackInfo,
+    /// Viewport in target pixels, clamped to the target
+    viewport: Rect<f32, ScreenSpace>,
+    pixel_rect: Rect<u32, ScreenSpace>,
+    callback: Arc<dyn EguiCallback>,
+}
+
+impl std::fmt::Debug for PreparedCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedCallback")
+            .field("before_group", &self.before_group)
+            .field("viewport", &self.viewport)
+            .field("pixel_rect", &self.pixel_rect)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Egui textures shared by several [`EguiPipe`]s, see [`EguiPipe::set_texture_store`]
+#[derive(Debug, Default, Clone)]
+pub struct TextureStore {
+    textures: HashMap<TextureId, Arc<Texture>>,
+    /// Bumped on every change so pipes know when to sync
+    generation: u64,
+}
+
+impl TextureStore {
+    /// A new empty store to share between pipes
+    pub fn shared() -> SharedTextureStore {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Ids of the textures in the store
+    pub fn ids(&self) -> impl Iterator<Item = TextureId> + '_ {
+        self.textures.keys().copied()
+    }
+}
+
+/// A [`TextureStore`] behind a lock, as each pipe holding it may be on its own thread
+pub type SharedTextureStore = Arc<Mutex<TextureStore>>;
+
+/// Texture changes queued while uploads are paused
+#[derive(Default)]
+struct PendingTextures(Vec<(TextureId, ImageDelta)>);
+
+/// CPU copies of the textures, see [`EguiPipe::set_retain_textures`]
+struct RetainedTextures(HashMap<TextureId, egui::ColorImage>);
+
+/// Hook choosing an egui texture's format from the one the pipe proposes
+#[derive(Default)]
+struct FormatHook(Option<Box<TextureFormatHook>>);
+type TextureFormatHook =
+    dyn Fn(TextureId, wgpu::TextureFormat) -> wgpu::TextureFormat + Send + Sync;
+
+impl std::fmt::Debug for FormatHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+impl std::fmt::Debug for PendingTextures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(id, d)| (id, d.pos, d.image.size())))
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for RetainedTextures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(id, image)| (id, image.size)))
+            .finish()
+    }
+}
+
+/// Adjustments to the colors [`EguiPipe`] writes, see [`EguiPipe::set_shader_settings`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShaderSettings {
+    /// Multiplies the output color, e.g. to raise the UI's white on HDR targets. Defaults to 1.
+    pub brightness: f32,
+    /// Gamma applied to the output color, above 1 brightens midtones. Defaults to 1.
+    pub gamma: f32,
+}
+
+impl Default for ShaderSettings {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            gamma: 1.0,
+        }
+    }
 }
 
-/// Uniform buffer for rendering
+/// Seconds after which the shader's clock starts again from 0, see [`EguiPipe::set_time`]
+pub const TIME_WRAP: f32 = 3600.0;
+
+/// Uniform buffer for rendering. Built from the [`ShaderSettings`] in `prepare`.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
 pub struct Uniform {
     screen_size_in_points: [f32; 2],
-    // padding as uniform buffers must be at least 16 bytes
-    _padding: [u32; 2],
+    /// 1 for [`ColorSpace::LinearTarget`]
+    linear_target: u32,
+    brightness: f32,
+    gamma: f32,
+    /// See [`DepthPrepass::max_depth`]
+    max_depth: f32,
+    /// Number of groups vs_layered spreads over the depth range
+    layers: u32,
+    /// 1 to round shape vertices to pixels, see [`EguiPipe::set_pixel_snapping`]
+    pixel_snap: u32,
+    /// Premultiplied color the output is multiplied by, see [`EguiPipe::set_tint`]
+    ui_tint: [f32; 4],
+    screen_size_in_pixels: [f32; 2],
+    /// See [`EguiPipe::set_rotation`]
+    rotation: [[f32; 2]; 2],
+    /// 1 to position vertices with `transform`, see [`EguiPipe::set_transform`]
+    transformed: u32,
+    /// Seconds for animated effects, see [`EguiPipe::set_time`]
+    time: f32,
+    transform: [[f32; 4]; 4],
+    /// 1 for RGB and 2 for BGR subpixel text, see [`EguiPipe::set_subpixel_text`]
+    subpixel: u32,
+    /// See [`EguiPipe::set_feather`]
+    feather: f32,
+    /// Points added to each vertex, see [`EguiPipe::set_displacement`]
+    displacement: [f32; 2],
+    /// See [`EguiPipe::set_ui_zoom`]
+    zoom: f32,
+    _padding: [u32; 3],
+}
+
+impl Uniform {
+    fn new(
+        settings: ShaderSettings,
+        screen_size_in_points: [f32; 2],
+        color_space: ColorSpace,
+    ) -> Self {
+        Self {
+            screen_size_in_points,
+            linear_target: (color_space == ColorSpace::LinearTarget) as u32,
+            brightness: settings.brightness,
+            gamma: settings.gamma,
+            max_depth: 0.0,
+            layers: 0,
+            pixel_snap: 0,
+            ui_tint: [1.0; 4],
+            screen_size_in_pixels: [1.0, 1.0],
+            rotation: ScreenRotation::None.matrix(),
+            transformed: 0,
+            time: 0.0,
+            subpixel: 0,
+            feather: 1.0,
+            displacement: [0.0; 2],
+            zoom: 1.0,
+            _padding: Default::default(),
+            transform: Transform3D::<f32, ScreenSpace, ScreenSpace>::identity().to_arrays(),
+        }
+    }
 }
 
 impl Deref for EguiPipe {
@@ -101,353 +1033,5066 @@ impl Deref for EguiPipe {
     }
 }
 
-impl<'a> Plumber<'a> for EguiPipe {
-    type PrepareContext = (
-        egui::TexturesDelta,
-        Vec<egui::ClippedPrimitive>,
-        ScreenDescriptor,
-    );
-    type Uniforms = Uniform;
+impl EguiPipe {
+    /// The groups prepared for the next render, in draw order
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
 
-    fn description() -> parrot::pipeline::PipelineDescription<'a> {
-        PipelineDescription {
-            vertex_layout: &VERTEX_LAYOUT,
-            pipeline_layout: Some(&[
-                Set(
-                    &[Binding {
-                        binding: BindingType::UniformBuffer,
-                        stage: wgpu::ShaderStages::VERTEX,
-                    }],
-                    Some("Egui screen bind group"),
-                ),
-                Set(
-                    &[
-                        Binding {
-                            binding: BindingType::Texture {
-                                multisampled: false,
-                            },
-                            stage: wgpu::ShaderStages::FRAGMENT,
-                        },
-                        Binding {
-                            binding: BindingType::Sampler,
-                            stage: wgpu::ShaderStages::FRAGMENT,
-                        },
-                    ],
-                    Some("Egui texture bind group"),
-                ),
-            ]),
-            shader: parrot::shader::ShaderFile::Wgsl(include_str!("./egui.wgsl")),
-            name: Some("Egui pipe"),
-        }
+    /// The scissor rect of each prepared group in draw order, in physical pixels. Consecutive groups
+    /// often share a rect.
+    ///
+    /// ```no_run
+    /// # fn overlay(pipe: &egui_pigeon::EguiPipe) {
+    /// let area: u32 = pipe.group_clip_rects().map(|r| r.area()).sum();
+    /// println!("egui clipped {} pixels this frame", area);
+    /// # }
+    /// ```
+    pub fn group_clip_rects(&self) -> impl Iterator<Item = Rect<u32, ScreenSpace>> + '_ {
+        self.groups.iter().map(|g| g.pixel_rect)
     }
 
-    fn setup(pipe: parrot::pipeline::Pipeline, paint: &parrot::Painter) -> Self {
-        let vertex_buffer =
-            paint.vertex_buffer::<egui::epaint::Vertex>(&[], Some("Egui vertex buffer"));
-        let index_buffer = paint.index_buffer_32(&[], Some("Egui index buffer"));
-        let egui_texture = HashMap::new();
-        let uniform_buffer =
-            paint.uniform_buffer(&[Uniform::default()], Some("Egui uniform buffer"));
-        let sampler = paint.sampler(
-            wgpu::FilterMode::Nearest,
-            wgpu::FilterMode::Linear,
-            Some("Egui texture sampler"),
-        );
-        let binding = paint.binding_group(
-            &pipe.layout.b_layouts[0],
-            &[&uniform_buffer],
-            Some("Egui uniform binding group"),
+    /// What each group prepared this frame draws, in draw order
+    pub fn draw_list(&self) -> impl Iterator<Item = DrawInfo> + '_ {
+        self.groups.iter().map(|g| DrawInfo {
+            index_count: g.range.len() as u32,
+            tex_id: g.tex_id,
+            pixel_rect: g.pixel_rect,
+            opaque: g.opaque,
+        })
+    }
+
+    /// Number of groups prepared this frame, the end of the ranges [`EguiPipe::render_range`] takes
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Draw only `groups` of the groups prepared this frame, e.g. the panels below a 3D scene and
+    /// then the windows and tooltips above it, finding where one ends with [`Group::primitive`].
+    /// The range is clamped to [`EguiPipe::group_count`]. Paint callbacks are drawn with the range
+    /// holding the group after them, those after the last group with the range ending there.
+    ///
+    /// The groups are drawn like `render` does without a stencil mask, depth prepass or debug view,
+    /// and never from render bundles. The pass keeps the last group's scissor rect, so reset it
+    /// before drawing anything else.
+    pub fn render_range<'a>(
+        &'a self,
+        paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        groups: std::ops::Range<usize>,
+    ) {
+        let end = groups.end.min(self.groups.len());
+        let groups = groups.start.min(end)..end;
+        let subpixel = self.subpixel_pipelines.as_ref().map(|p| &p.1);
+        self.draw_range(
+            paint,
+            pass,
+            &self.core.pipeline.wgpu,
+            None,
+            subpixel,
+            groups,
         );
-        let core = PipelineCore {
-            pipeline: pipe,
-            bindings: vec![binding],
-            uniforms: vec![uniform_buffer],
-        };
-        let container = None;
+    }
 
-        Self {
-            vertex_buffer,
-            index_buffer,
-            egui_texture,
-            groups: vec![],
-            sampler,
-            container,
-            core,
+    /// Recompile the pipeline from new WGSL source, keeping the buffers and textures. The source must
+    /// have the same bindings as the built in shader, and every entry point the pipe's options can
+    /// draw with: `vs_main`, `vs_layered`, `vs_fullscreen`, `fs_main`, `fs_blit` and `fs_overdraw`,
+    /// plus `fs_coverage` with [`EguiPipeBuilder::coverage_mask`] and `fs_subpixel_mask` and
+    /// `fs_subpixel_color` with the subpixel feature.
+    ///
+    /// The pipeline is built in a wgpu error scope, so a shader that doesn't fit the pipe's layout
+    /// is returned as an error rather than panicking. On any error the current pipeline is left
+    /// untouched.
+    pub fn reload_shader(&mut self, paint: &Painter, wgsl_source: &str) -> Result<(), EguiError> {
+        validate_wgsl(wgsl_source, &self.entry_points())?;
+
+        let device = &paint.device.wgpu;
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Egui shader"),
+            source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+        });
+        // Reuse the existing layout so the bind groups stay valid
+        let pipeline = egui_render_pipeline(
+            &paint.device,
+            &self.core.pipeline.layout,
+            &self.core.pipeline.vertex_layout,
+            &shader,
+            self.pass_variant(paint),
+            Some("Egui pipe"),
+        );
+        if let Some(e) = poll_ready(device.pop_error_scope()).flatten() {
+            return Err(EguiError::ShaderCompile(e.to_string()));
         }
+        self.shader = shader;
+        self.core.pipeline.wgpu = pipeline;
+        self.layer_pipeline = None;
+        self.overdraw_pipeline = None;
+        self.stencil_pipeline = None;
+        self.prepass_pipelines = None;
+        self.subpixel_pipelines = None;
+        self.blit_pipeline = None;
+        self.composite_pipeline = None;
+        self.bundles = None;
+        Ok(())
     }
 
-    fn prepare(
-        &'a mut self,
-        context: Self::PrepareContext,
-        paint: &mut parrot::Painter,
-    ) -> Vec<(&'a mut UniformBuffer, Vec<Self::Uniforms>)> {
-        let mut vertices: Vec<egui::epaint::Vertex> = vec![];
-        let mut indices: Vec<u32> = vec![];
-        let mut groups: Vec<Group> = vec![];
-        let pixels_per_point = context.2.pixels_per_point;
-        let size_in_pixels = context.2.size_in_pixels;
-        for primative in context.1 {
-            match primative.primitive {
-                egui::epaint::Primitive::Callback(_) => {
-                    log::warn!("Callback not implemented");
-                    continue;
+    /// Set the debug visualisation used by the next renders. Can be changed every frame.
+    pub fn set_debug_mode(&mut self, mode: DebugMode) {
+        self.debug_mode = mode;
+    }
+
+    /// The current debug visualisation
+    pub fn debug_mode(&self) -> DebugMode {
+        self.debug_mode
+    }
+
+    /// Choose how clip rects are rounded to pixels, from the next `prepare`
+    pub fn set_clip_rounding(&mut self, rounding: ClipRounding) {
+        self.clip_rounding = rounding;
+    }
+
+    /// How clip rects are rounded to pixels
+    pub fn clip_rounding(&self) -> ClipRounding {
+        self.clip_rounding
+    }
+
+    /// Choose what's done with primitives whose clip rect has a NaN edge, from the next `prepare`.
+    /// The first one is logged with its rect.
+    pub fn set_nan_clip(&mut self, nan_clip: NanClip) {
+        self.nan_clip = nan_clip;
+    }
+
+    /// What's done with NaN clip rects, see [`EguiPipe::set_nan_clip`]
+    pub fn nan_clip(&self) -> NanClip {
+        self.nan_clip
+    }
+
+    /// Draw a cursor from `sheet` above the UI, or stop drawing it with `None`. Set where with
+    /// [`EguiPipe::set_cursor`]. See the [`cursor`] module.
+    pub fn set_software_cursor(&mut self, sheet: Option<CursorSheet>) {
+        self.software_cursor = sheet;
+        self.cursor_dirty = true;
+    }
+
+    /// The software cursor's sprites, if it is enabled
+    pub fn software_cursor(&self) -> Option<&CursorSheet> {
+        self.software_cursor.as_ref()
+    }
+
+    /// Where the next `prepare` draws the software cursor, in points, and which icon it uses, e.g.
+    /// egui's hover position and [`egui::PlatformOutput::cursor_icon`]. `None` hides it.
+    pub fn set_cursor(&mut self, pos: Option<egui::Pos2>, icon: egui::CursorIcon) {
+        self.cursor = pos.map(|p| (p, icon));
+    }
+
+    /// Only draw where the render pass' stencil attachment passes `mask`, e.g. to clip the UI to a
+    /// shape written into the stencil by an earlier pass. The pass `render` is given must then have
+    /// a depth stencil attachment of `mask.format`. [`DebugMode::Overdraw`] and render bundles are
+    /// ignored while masking and [`EguiPipe::render_layer`] is never masked.
+    pub fn set_stencil_mask(&mut self, mask: Option<StencilMask>) {
+        self.stencil_mask = mask;
+    }
+
+    /// The current stencil test
+    pub fn stencil_mask(&self) -> Option<StencilMask> {
+        self.stencil_mask
+    }
+
+    /// Draw the fully opaque parts of the UI front to back with depth writes before everything
+    /// else, so hidden UI and scene pixels are rejected by early depth testing on tile based GPUs.
+    ///
+    /// `prepare` then splits meshes into opaque and blended groups. Only solid triangles using
+    /// the font atlas' white texel are treated as opaque; anything uncertain, like textured or
+    /// anti-aliased triangles, is blended as usual; [`EguiPipe::draw_list`] shows which groups
+    /// were found opaque, to judge how much a layout gains. Each frame, in a pass with a depth attachment
+    /// of `prepass.format` cleared to 1:
+    /// 1. call [`EguiPipe::render_opaque`]
+    /// 2. draw the scene with depth testing at depths above `prepass.max_depth`
+    /// 3. call `render` for the rest of the UI.
+    ///
+    /// Render bundles and [`DebugMode::Overdraw`] are ignored while this is on, and a stencil mask
+    /// takes precedence over it. Takes effect from the next `prepare`.
+    pub fn set_depth_prepass(&mut self, prepass: Option<DepthPrepass>) {
+        self.depth_prepass = prepass;
+    }
+
+    /// The current depth prepass
+    pub fn depth_prepass(&self) -> Option<DepthPrepass> {
+        self.depth_prepass
+    }
+
+    /// Draw the opaque groups for the depth prepass. Does nothing unless
+    /// [`EguiPipe::set_depth_prepass`] is on.
+    pub fn render_opaque<'a>(&'a mut self, paint: &Painter, pass: &mut wgpu::RenderPass<'a>) {
+        if self.stencil_mask.is_some() {
+            return;
+        }
+        self.update_prepass_pipelines(paint);
+        let pipe: &'a Self = self;
+        if let Some((_, opaque, _)) = &pipe.prepass_pipelines {
+            pipe.draw(paint, pass, opaque, Some(true), None);
+        }
+    }
+
+    /// Change the color adjustments, from the next `prepare`
+    pub fn set_shader_settings(&mut self, settings: ShaderSettings) {
+        self.shader_settings = settings;
+        self.uniform_dirty = true;
+    }
+
+    /// The current color adjustments
+    pub fn shader_settings(&self) -> ShaderSettings {
+        self.shader_settings
+    }
+
+    /// Multiply the whole UI by `tint` from the next `prepare`, e.g. `Rgba::from_white_alpha(0.5)`
+    /// to fade it to half opacity. The tint is premultiplied like the UI, so fading the alpha fades
+    /// the colors with it rather than washing them out. White by default.
+    pub fn set_tint(&mut self, tint: egui::Rgba) {
+        self.tint = tint;
+        self.uniform_dirty = true;
+    }
+
+    /// The color the UI is multiplied by
+    pub fn tint(&self) -> egui::Rgba {
+        self.tint
+    }
+
+    /// Fill the screen with `color` under the UI from the next `prepare`, for apps where egui is the
+    /// whole window. The fill is a quad drawn with the font atlas' white texel before the first
+    /// group, so the target needs no clear of its own and a pass shared with other drawing can
+    /// still give the UI a flat backdrop. `None` by default, which draws only what egui tessellated.
+    pub fn set_background(&mut self, color: Option<egui::Color32>) {
+        self.background = color;
+    }
+
+    /// The color drawn under the UI, see [`EguiPipe::set_background`]
+    pub fn background(&self) -> Option<egui::Color32> {
+        self.background
+    }
+
+    /// Round the vertices of untextured shapes to whole pixels from the next `prepare`, so 1 pixel
+    /// separators and strokes stay crisp at fractional scales like 125%. Text and images keep their
+    /// exact positions. Off by default, which draws exactly what egui tessellated.
+    pub fn set_pixel_snapping(&mut self, enabled: bool) {
+        self.pixel_snapping = enabled;
+        self.uniform_dirty = true;
+    }
+
+    /// Whether shapes are snapped to pixels, see [`EguiPipe::set_pixel_snapping`]
+    pub fn pixel_snapping(&self) -> bool {
+        self.pixel_snapping
+    }
+
+    /// Scale the antialiasing ramp egui feathers the edges of shapes with from the next `prepare`.
+    /// Below 1 the ramp is narrowed around its middle for crisper edges, down to aliased ones near
+    /// 0; above 1 it's flattened for softer ones, though never past the pixel egui feathered. The
+    /// ramp is found from how fast the vertex alpha changes, so it's only exact when rendering at
+    /// the descriptor's scale. Text and images are untouched. 1 by default, egui's own look.
+    pub fn set_feather(&mut self, feather: f32) {
+        self.feather = feather.max(0.001);
+        self.uniform_dirty = true;
+    }
+
+    /// How shape edges' antialiasing is scaled, see [`EguiPipe::set_feather`]
+    pub fn feather(&self) -> f32 {
+        self.feather
+    }
+
+    /// Give the shader `time` in seconds from the next `prepare` instead of the time since the pipe
+    /// was created, e.g. to replay a recording deterministically. `None` goes back to the clock.
+    ///
+    /// Custom shaders read it as `r_locals.time`, e.g. for pulsing highlights. The clock wraps
+    /// every [`TIME_WRAP`] seconds so it keeps its precision in long sessions; effects with a
+    /// period dividing that don't jump when it does. With [`EguiPipeBuilder::static_screen`] the
+    /// time only advances when the uniform is uploaded.
+    pub fn set_time(&mut self, time: Option<f32>) {
+        self.time = time;
+        self.uniform_dirty = true;
+    }
+
+    /// The time given to the shader by the last `prepare`
+    pub fn time(&self) -> f32 {
+        self.uniform.time
+    }
+
+    /// Draw glyphs with a coverage per color channel for LCD screens with `order` subpixels, from
+    /// the next `prepare`. `None` goes back to grayscale antialiasing.
+    ///
+    /// wgpu 0.13 has no dual source blending, so glyph runs are drawn twice: once darkening the
+    /// target by each channel's coverage, then adding the text color. That costs an extra draw
+    /// per run of glyphs and render bundles aren't used. Only [`Render::render`] draws subpixel
+    /// text; it needs an opaque target with horizontal subpixels at the UI's scale, so layers,
+    /// scaled renders, stencil masks, depth prepasses and the coverage mask draw grayscale text.
+    /// Glyphs that overlap each other can show slight fringes.
+    ///
+    /// [`Render::render`]: pigeon_2d::pipeline::Render::render
+    #[cfg(feature = "subpixel")]
+    pub fn set_subpixel_text(&mut self, order: Option<SubpixelOrder>) {
+        self.subpixel_text = order;
+        self.uniform_dirty = true;
+    }
+
+    /// The subpixel order glyphs are drawn with, see [`EguiPipe::set_subpixel_text`]
+    #[cfg(feature = "subpixel")]
+    pub fn subpixel_text(&self) -> Option<SubpixelOrder> {
+        self.subpixel_text
+    }
+
+    // The uniform's subpixel mode: 0 for grayscale text, 1 for RGB and 2 for BGR
+    fn subpixel_mode(&self) -> u32 {
+        #[cfg(feature = "subpixel")]
+        if !self.coverage_mask {
+            return match self.subpixel_text {
+                None => 0,
+                Some(SubpixelOrder::Rgb) => 1,
+                Some(SubpixelOrder::Bgr) => 2,
+            };
+        }
+        0
+    }
+
+    /// Turn the UI on its target from the next `prepare`, for displays mounted rotated. The
+    /// [`ScreenDescriptor`] still describes the UI as egui lays it out, so for quarter turns the
+    /// target is `size_in_pixels` with width and height swapped. Scissors are rotated to match.
+    /// Mapping input into the UI and drawing paint callbacks rotated is left to the application;
+    /// callbacks get the rotated viewport.
+    pub fn set_rotation(&mut self, rotation: ScreenRotation) {
+        self.rotation = rotation;
+        self.uniform_dirty = true;
+    }
+
+    /// How the UI is turned on its target
+    pub fn rotation(&self) -> ScreenRotation {
+        self.rotation
+    }
+
+    /// Draw the UI mirrored top to bottom from the next `prepare`, for rendering into a texture
+    /// with [`EguiPipe::render_layer`] that is sampled with OpenGL style uvs, 0 at the bottom. The
+    /// first row of the texture then holds the bottom of the UI. Scissors are flipped to match and
+    /// the flip applies after [`EguiPipe::set_rotation`]. Paint callbacks get the flipped viewport
+    /// but draw their own content upright, and a [`EguiPipe::set_transform`] isn't flipped. Off by
+    /// default, leave it off for rendering to the screen.
+    pub fn set_flip_y(&mut self, flip_y: bool) {
+        self.flip_y = flip_y;
+        self.uniform_dirty = true;
+    }
+
+    /// Whether the UI is drawn upside down, see [`EguiPipe::set_flip_y`]
+    pub fn flip_y(&self) -> bool {
+        self.flip_y
+    }
+
+    /// Move the whole UI by `offset` points from the next `prepare`, e.g. to shake it for a few
+    /// frames when the player takes damage. The offset is added to every vertex in the shader, so
+    /// egui's layout and input are untouched. Set a new offset each frame to animate it, e.g. from
+    /// a decaying amplitude and [`EguiPipe::time`]. Paint callbacks move with the UI.
+    pub fn set_displacement(&mut self, offset: egui::Vec2) {
+        self.displacement = offset;
+        self.uniform_dirty = true;
+    }
+
+    /// Points the UI is moved by, see [`EguiPipe::set_displacement`]
+    pub fn displacement(&self) -> egui::Vec2 {
+        self.displacement
+    }
+
+    /// Whether the scissors move with a [`EguiPipe::set_displacement`], on by default. Panels
+    /// clipped where they were before moving are cut off at their edges, turn it off to keep the
+    /// clip rects in place, e.g. for a UI displaced inside a fixed frame.
+    pub fn set_displace_scissors(&mut self, displace: bool) {
+        self.displace_scissors = displace;
+    }
+
+    /// Whether the scissors move with the UI, see [`EguiPipe::set_displace_scissors`]
+    pub fn displace_scissors(&self) -> bool {
+        self.displace_scissors
+    }
+
+    /// Draw the UI placed in a world by `transform`, e.g. on a screen in a 2D game, rather than
+    /// over the target, from the next `prepare`. `None` goes back to drawing over the target.
+    ///
+    /// The [`ScreenDescriptor`] then describes the UI's own size for layout, not the target.
+    /// Scissors are axis aligned in the target, so clip rects can't follow the transform and
+    /// aren't applied: anything egui clips, like the inside of scroll areas, draws in full. Paint
+    /// callbacks, rotation and pixel snapping are skipped too. For a transformed UI that clips
+    /// exactly, render it into a texture with [`EguiPipe::render_layer`] and draw that instead,
+    /// e.g. with [`panel::PanelQuad`].
+    pub fn set_transform(&mut self, transform: Option<WorldTransform>) {
+        self.transform = transform;
+        self.uniform_dirty = true;
+    }
+
+    /// Where the UI is placed in a world, see [`EguiPipe::set_transform`]
+    pub fn transform(&self) -> Option<WorldTransform> {
+        self.transform
+    }
+
+    /// Draw the UI into `rect` of the target, in physical pixels, instead of over all of it, e.g.
+    /// the game's area between letterbox bars. `None` draws over the whole target again.
+    ///
+    /// The [`ScreenDescriptor`] then describes `rect` rather than the target: give it and egui the
+    /// rect's size, and pointer positions relative to its corner. The UI is drawn into the rect
+    /// through the viewport and every scissor is moved into it, so nothing is drawn on the bars.
+    /// The rect must lie inside the target, and clip rects returned by
+    /// [`EguiPipe::group_clip_rects`] are in the target's pixels. Takes effect from the next
+    /// `prepare`.
+    pub fn set_destination(&mut self, rect: Option<Rect<u32, ScreenSpace>>) {
+        self.destination = rect;
+    }
+
+    /// Part of the target the UI is drawn into, see [`EguiPipe::set_destination`]
+    pub fn destination(&self) -> Option<Rect<u32, ScreenSpace>> {
+        self.destination
+    }
+
+    // Part of the target the UI covers, in its pixels
+    fn target_rect(&self) -> Rect<u32, ScreenSpace> {
+        self.destination
+            .unwrap_or_else(|| Rect::from_size(self.render_size))
+    }
+
+    // Map the UI onto its part of the target
+    fn set_ui_viewport(&self, pass: &mut wgpu::RenderPass<'_>) {
+        let v = self.target_rect().to_f32();
+        pass.set_viewport(v.origin.x, v.origin.y, v.width(), v.height(), 0.0, 1.0);
+    }
+
+    /// Write the uniform built by the last `prepare` into its buffer. `prepare` returns the uniform
+    /// for the framework to upload; call this instead when calling `prepare` and `render` yourself
+    /// without `pigeon_2d`, between the two.
+    pub fn upload_uniforms(&mut self, paint: &mut Painter) {
+        paint
+            .device
+            .update_buffer(&[self.uniform], &mut self.core.uniforms[0]);
+    }
+
+    /// Upload the uniform in the next `prepare`. With [`EguiPipeBuilder::static_screen`] call this
+    /// whenever the screen size, scale or `color_space` changes.
+    pub fn invalidate_uniform(&mut self) {
+        self.uniform_dirty = true;
+    }
+
+    /// Pause or resume texture uploads, e.g. to keep the frame budget while a heavy scene loads.
+    ///
+    /// While paused `prepare` queues texture changes without touching the GPU, merging those for the
+    /// same texture so the queue only grows with the number of textures. They are applied at the
+    /// start of the first `prepare` after resuming, so everything that changed is one frame late at
+    /// best. In the meantime new textures are skipped or drawn with the
+    /// [fallback](EguiPipe::with_fallback_texture), and text using glyphs added to the font atlas
+    /// shows whatever was in that part of the atlas before.
+    pub fn set_uploads_enabled(&mut self, enabled: bool) {
+        self.uploads_enabled = enabled;
+    }
+
+    /// Whether texture uploads are enabled, see [`EguiPipe::set_uploads_enabled`]
+    pub fn uploads_enabled(&self) -> bool {
+        self.uploads_enabled
+    }
+
+    /// Create the font atlas texture at `size` ahead of the first `prepare`, so that frame only
+    /// copies the atlas in instead of also creating the texture and its bind group. Use the size
+    /// egui will report, e.g. `ctx.fonts().font_image_size()` after a first `ctx.run`; an atlas of
+    /// a different size is created again as usual.
+    pub fn reserve_font_atlas(&mut self, paint: &Painter, size: [u32; 2]) -> Result<(), EguiError> {
+        let id = FONT_ATLAS_ID;
+        if self.egui_texture.get(&id).map(|t| t.0.size) == Some(Size2D::new(size[0], size[1])) {
+            return Ok(());
+        }
+        self.create_texture(paint, id, Size2D::new(size[0], size[1]))
+    }
+
+    /// Share textures with every other pipe using `store`, e.g. to draw the popups of a context
+    /// with a second pipe without uploading its font atlas twice. Textures this pipe already has
+    /// are added to the store. Give the textures delta to one pipe only and prepare it first
+    /// each frame, the others pick up its changes in their next `prepare`. Managed texture ids are
+    /// only unique within one [`egui::Context`], so don't share a store between contexts.
+    pub fn set_texture_store(&mut self, store: Option<SharedTextureStore>) {
+        self.texture_store = store.map(|store| {
+            if let Ok(mut s) = store.lock() {
+                for (id, (tex, _)) in &self.egui_texture {
+                    s.textures.entry(*id).or_insert_with(|| tex.clone());
                 }
-                egui::epaint::Primitive::Mesh(mut mesh) => {
-                    let si = indices.len() as u32;
-                    let si2 = vertices.len() as u32;
+                s.generation += 1;
+            }
+            (store, 0)
+        });
+    }
+
+    /// The store textures are shared through, see [`EguiPipe::set_texture_store`]
+    pub fn texture_store(&self) -> Option<&SharedTextureStore> {
+        self.texture_store.as_ref().map(|s| &s.0)
+    }
+
+    /// Resources shared by every [`EguiCallback`], e.g. to set them up before the first frame
+    pub fn callback_resources(&mut self) -> &mut CallbackResources {
+        &mut self.callback_resources
+    }
+
+    /// Keep texture `id` when egui frees it, e.g. a logo registered once at startup whose id egui
+    /// may reuse. It can be pinned before it is first uploaded. Egui freeing a pinned texture is
+    /// logged as a warning.
+    pub fn pin_texture(&mut self, id: TextureId) {
+        self.pinned_textures.insert(id);
+    }
+
+    /// Let egui free texture `id` again, see [`EguiPipe::pin_texture`]
+    pub fn unpin_texture(&mut self, id: TextureId) {
+        self.pinned_textures.remove(&id);
+    }
+
+    /// Whether texture `id` is pinned, see [`EguiPipe::pin_texture`]
+    pub fn is_texture_pinned(&self, id: TextureId) -> bool {
+        self.pinned_textures.contains(&id)
+    }
+
+    /// Keep at most `max` textures, e.g. for a grid of thumbnails. Creating one past the cap first
+    /// evicts the textures drawn the longest ago, other than the font atlas, pinned textures and the
+    /// cursor. egui doesn't know about evictions, so an evicted texture draws with the fallback
+    /// texture until it is sent again. `None`, the default, keeps every texture.
+    pub fn set_max_textures(&mut self, max: Option<usize>) {
+        self.max_textures = max;
+    }
+
+    /// Keep a CPU copy of every texture as egui sends it, so [`EguiPipe::recreate`] can upload
+    /// them again after the device is lost. It costs as much memory again as the textures, the
+    /// font atlas alone is a few MB. Off by default; turning it off drops the copies.
+    pub fn set_retain_textures(&mut self, retain: bool) {
+        if retain != self.retained_textures.is_some() {
+            self.retained_textures = retain.then(|| RetainedTextures(HashMap::new()));
+        }
+    }
+
+    /// Whether CPU copies of the textures are kept, see [`EguiPipe::set_retain_textures`]
+    pub fn retain_textures(&self) -> bool {
+        self.retained_textures.is_some()
+    }
+
+    /// The most textures kept, see [`EguiPipe::set_max_textures`]
+    pub fn max_textures(&self) -> Option<usize> {
+        self.max_textures
+    }
+
+    /// Drop every texture and its binding, e.g. after replacing the [`egui::Context`], whose ids
+    /// no longer mean anything. The next `TexturesDelta` creates the textures again. Changes queued
+    /// while uploads are paused are dropped too, and textures in a [`TextureStore`] are removed from
+    /// it. Pins are kept.
+    pub fn clear_textures(&mut self) {
+        let ids: Vec<TextureId> = self.egui_texture.keys().copied().collect();
+        for id in ids {
+            self.remove_texture(id);
+        }
+        self.pending_textures.0.clear();
+        self.pending_free.clear();
+        // The cursor isn't egui's, upload it again
+        self.cursor_dirty = true;
+    }
+
+    /// Scale the whole UI by `scale` on top of the OS scale factor, e.g. for a UI scale slider.
+    /// `prepare` applies it to the [`ScreenDescriptor`] with [`ScreenDescriptor::with_ui_scale`],
+    /// so pass the descriptor with the OS scale as usual.
+    ///
+    /// egui has to lay out and tessellate at the same combined scale for text to stay sharp: give it
+    /// `pixels_per_point` and `screen_rect()` of `descriptor.with_ui_scale(scale)`. The pipe warns
+    /// when the clip rects it gets don't fit that scale.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = scale;
+        self.uniform_dirty = true;
+    }
+
+    /// The UI scale, see [`EguiPipe::set_ui_scale`]
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Zoom the drawn UI by `zoom` around the middle of the screen, 1.0 by default. The vertex
+    /// shader scales the positions and the scissors scale with them, so it's cheap to animate.
+    ///
+    /// Unlike [`EguiPipe::set_ui_scale`] this is render only: egui still lays out and tessellates
+    /// at the descriptor's scale, so text is stretched rather than sharp, and pointer positions
+    /// have to be unzoomed by the app, `center + (pos - center) / zoom`, before they reach egui.
+    pub fn set_ui_zoom(&mut self, zoom: f32) {
+        self.ui_zoom = zoom;
+        self.uniform_dirty = true;
+    }
+
+    /// The render only zoom, see [`EguiPipe::set_ui_zoom`]
+    pub fn ui_zoom(&self) -> f32 {
+        self.ui_zoom
+    }
+
+    /// Draw the UI at `scale` times the resolution of the target, e.g. 0.75 to save fill rate on a
+    /// high resolution screen. `prepare` multiplies the [`ScreenDescriptor::render_scale`] by it,
+    /// so the clip rects and uniform use the scaled size while egui still lays out in points.
+    ///
+    /// Render with [`EguiPipe::render_scaled`] when this isn't 1.0, which draws into an offscreen
+    /// target and filters it onto the real one.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale;
+        self.uniform_dirty = true;
+    }
+
+    /// The render scale, see [`EguiPipe::set_render_scale`]
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Record the draws into render bundles and replay them while the prepared UI stays the same,
+    /// e.g. for a static pause menu. Any change to the geometry, groups or set of textures records
+    /// them again. Only used by `render` without a debug mode.
+    ///
+    /// Bundles must match the pass they are replayed in, so the pass must have a single
+    /// `color_space.target_format()` attachment with the painter's sample count and no depth
+    /// attachment.
+    pub fn set_render_bundles(&mut self, enabled: bool) {
+        self.render_bundles = enabled;
+        self.bundles = None;
+    }
+
+    /// Whether render bundles are used, see [`EguiPipe::set_render_bundles`]
+    pub fn render_bundles(&self) -> bool {
+        self.render_bundles
+    }
+
+    /// Record the draws of the prepared UI into a single render bundle, to replay with
+    /// [`EguiPipe::execute_bundle`] in passes of your own, e.g. to draw the same UI into several
+    /// targets without recording it each time. A bundle can't set scissor rects, so clip rects aren't applied;
+    /// [`EguiPipe::set_render_bundles`] keeps a bundle per clip rect instead. The pass has the
+    /// same requirements as for `set_render_bundles`. Debug modes, the stencil mask, paint
+    /// callbacks and frames split into chunks aren't recorded.
+    ///
+    /// The bundle refers to the pipe's buffers and textures as they are now. Record it again after
+    /// any `prepare` that changed the geometry, grew a buffer or set or freed a texture, and after
+    /// anything else that replaces the pipe's bindings, such as [`EguiPipe::reload_shader`] or
+    /// [`EguiPipe::recreate`]. A stale bundle draws the old UI, or fails validation once what it
+    /// refers to is dropped.
+    pub fn record_bundle(&self, paint: &Painter) -> wgpu::RenderBundle {
+        let run: Vec<_> = self
+            .groups
+            .iter()
+            .filter(|g| !g.pixel_rect.is_empty())
+            .filter_map(|g| Some((g, self.group_binding(g)?)))
+            .collect();
+        self.encode_bundle(paint, &run, &mut RenderStats::default())
+    }
+
+    /// Replay a bundle from [`EguiPipe::record_bundle`] in `pass`, in the target rect the pipe
+    /// draws the UI in
+    pub fn execute_bundle<'a>(
+        &self,
+        pass: &mut wgpu::RenderPass<'a>,
+        bundle: &'a wgpu::RenderBundle,
+    ) {
+        let rect = self.target_rect();
+        if rect.is_empty() {
+            return;
+        }
+        self.set_ui_viewport(pass);
+        pass.set_scissor_rect(rect.origin.x, rect.origin.y, rect.width(), rect.height());
+        pass.execute_bundles(std::iter::once(bundle));
+        self.mark_drawn(self.groups.iter().map(|g| g.tex_id));
+    }
+
+    /// Bytes of vertex data the last `prepare` uploaded. When only colors changed since the frame
+    /// before, e.g. a hover highlight, only the changed colors are written.
+    pub fn last_vertex_upload_bytes(&self) -> usize {
+        self.vertex_upload_bytes
+    }
+
+    /// Capacity of the vertex and index buffers in bytes. They grow to fit the largest frame so far.
+    pub fn buffer_memory_bytes(&self) -> (usize, usize) {
+        let index_bytes = match &self.index_buffer {
+            EguiIndexBuffer::U16(b) => b.size as usize * std::mem::size_of::<u16>(),
+            EguiIndexBuffer::U32(b) => b.size as usize * std::mem::size_of::<u32>(),
+        };
+        (self.vertex_buffer.size as usize, index_bytes)
+    }
+
+    /// Capacity of the vertex and index buffers in vertices and indices. They are reallocated when
+    /// a frame doesn't fit, which `prepare` logs at debug level.
+    pub fn buffer_capacities(&self) -> (usize, usize) {
+        let indices = match &self.index_buffer {
+            EguiIndexBuffer::U16(b) => b.size as usize,
+            EguiIndexBuffer::U32(b) => b.size as usize,
+        };
+        (
+            self.vertex_buffer.size as usize / std::mem::size_of::<Vertex>(),
+            indices,
+        )
+    }
+
+    /// Choose when the vertex and index buffers are reallocated smaller, e.g. to give back the memory
+    /// a heavy window used once it's closed. Shrinking reallocates, so the default never does.
+    pub fn set_shrink_policy(&mut self, policy: ShrinkPolicy) {
+        self.shrink_policy = policy;
+        self.underused_frames = 0;
+    }
+
+    /// When the buffers are reallocated smaller
+    pub fn shrink_policy(&self) -> ShrinkPolicy {
+        self.shrink_policy
+    }
+
+    /// Submit the queue and block until the GPU has finished everything submitted, including
+    /// pending buffer mappings. For tests and one off readbacks like [`selftest()`], not for every
+    /// frame as it stalls the CPU on the GPU.
+    pub fn submit_and_poll(&self, paint: &Painter) {
+        paint.device.queue.submit(None);
+        paint.device.wgpu.poll(wgpu::Maintain::Wait);
+    }
+
+    /// Copy texture `id` back to the CPU as RGBA pixels, row by row, waiting for the GPU. `None`
+    /// for unknown ids, textures from [`EguiPipe::register_wgpu_texture`], or if the read fails.
+    /// For tests and tools, like [`EguiPipe::submit_and_poll`] it stalls the CPU on the GPU.
+    pub fn read_texture(
+        &self,
+        paint: &Painter,
+        id: TextureId,
+    ) -> Option<(Size2D<u32, ScreenSpace>, Vec<Rgba8>)> {
+        let (tex, _) = self.egui_texture.get(&id)?;
+        let pixels = read_back(paint, tex, "Egui texture readback buffer")?
+            .into_iter()
+            .map(|[r, g, b, a]| Rgba8::new(r, g, b, a))
+            .collect();
+        Some((tex.size, pixels))
+    }
+
+    /// What the renders since the last `prepare` recorded into their passes, added up, so every
+    /// [`EguiPipe::render_range`] of a frame counts
+    pub fn last_render_stats(&self) -> RenderStats {
+        self.render_stats.lock().map(|s| *s).unwrap_or_default()
+    }
+
+    /// Warn when a frame issues more than `budget` draw calls, e.g. on tile based mobile GPUs
+    /// that slow down sharply past a threshold. Each distinct clip rect and texture costs a draw,
+    /// so a layout split into many clipped regions shows up here. It's logged when a frame first
+    /// goes over and again after one comes back under; assert on
+    /// [`RenderStats::draw_calls`] from [`EguiPipe::last_render_stats`] to fail hard instead.
+    /// `None`, the default, never warns.
+    pub fn set_draw_call_budget(&mut self, budget: Option<u32>) {
+        self.draw_call_budget = budget;
+        self.over_budget.store(false, Ordering::Relaxed);
+    }
+
+    /// Draw calls a render may issue before warning, see [`EguiPipe::set_draw_call_budget`]
+    pub fn draw_call_budget(&self) -> Option<u32> {
+        self.draw_call_budget
+    }
+
+    // Stamp the textures `ids` as drawn in this frame, for evict_textures
+    fn mark_drawn(&self, ids: impl IntoIterator<Item = TextureId>) {
+        if let Ok(mut used) = self.texture_last_used.lock() {
+            for id in ids {
+                used.insert(id, self.texture_frame);
+            }
+        }
+    }
+
+    // Add the counters of a render to this frame's for last_render_stats, and check them against
+    // the budget
+    fn record_stats(&self, stats: RenderStats) {
+        let total = match self.render_stats.lock() {
+            Ok(mut frame) => {
+                frame.draw_calls += stats.draw_calls;
+                frame.scissor_changes += stats.scissor_changes;
+                frame.binding_changes += stats.binding_changes;
+                frame.skipped_groups += stats.skipped_groups;
+                *frame
+            }
+            Err(_) => stats,
+        };
+        if let Some(budget) = self.draw_call_budget.filter(|&b| total.draw_calls > b) {
+            if !self.over_budget.swap(true, Ordering::Relaxed) {
+                log::warn!(
+                    "Egui frame is over its draw call budget >> Draw calls: {}, Budget: {}",
+                    total.draw_calls,
+                    budget
+                );
+            }
+        }
+    }
+
+    // Start counting the renders of a new frame, warning again about the budget once a frame came
+    // back under it
+    fn reset_stats(&mut self) {
+        let finished = self
+            .render_stats
+            .get_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        if !matches!(self.draw_call_budget, Some(b) if finished.draw_calls > b) {
+            self.over_budget.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the errors the pipe recovered from since the last call, oldest first, e.g. to show
+    /// them to the user. `prepare` can't return them, so textures that failed to upload, skipped
+    /// frames and paint callbacks, and options the device doesn't support are kept here as well
+    /// as logged. An error already waiting isn't added again, so a problem repeating every frame
+    /// is taken once.
+    pub fn take_errors(&mut self) -> Vec<EguiError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Keep an error for take_errors
+    fn push_error(&mut self, error: EguiError) {
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Format of the attachment [`Render::render`] draws into, set by the [`ColorSpace`]
+    ///
+    /// [`Render::render`]: pigeon_2d::pipeline::Render::render
+    pub fn target_format(&self) -> wgpu::TextureFormat {
+        self.color_space.target_format()
+    }
+
+    /// Samples per pixel of the attachment [`Render::render`] draws into, the painter's sample
+    /// count when the pipe was created
+    ///
+    /// [`Render::render`]: pigeon_2d::pipeline::Render::render
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Check a target with `format` and `sample_count` samples can be rendered into, e.g. before
+    /// beginning the pass, so a mismatch is reported as [`EguiError::TargetMismatch`] instead of a
+    /// wgpu validation error that never mentions egui. Debug builds check the painter's sample
+    /// count on every render, as the pass would have it.
+    pub fn validate_target(
+        &self,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Result<(), EguiError> {
+        let expected = (self.target_format(), self.sample_count);
+        if (format, sample_count) != expected {
+            return Err(EguiError::TargetMismatch {
+                expected,
+                got: (format, sample_count),
+            });
+        }
+        Ok(())
+    }
+
+    /// Start timing the GPU work recorded into `encoder` from here, e.g. before beginning the pass
+    /// egui is rendered in. Does nothing unless the device has [`wgpu::Features::TIMESTAMP_QUERY`].
+    /// [`EguiPipe::render_layer`] times itself.
+    pub fn begin_gpu_timer(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.begin(encoder);
+        }
+    }
+
+    /// Stop timing, after the pass egui is rendered in has ended
+    pub fn end_gpu_timer(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.end(encoder);
+        }
+    }
+
+    /// GPU time between the last pair of [`EguiPipe::begin_gpu_timer`] and
+    /// [`EguiPipe::end_gpu_timer`] calls that has been read back. Results are read back
+    /// asynchronously in `prepare`, so they lag a couple of frames behind. `None` without
+    /// [`wgpu::Features::TIMESTAMP_QUERY`] or before the first measurement.
+    pub fn last_gpu_time(&self) -> Option<std::time::Duration> {
+        self.gpu_timer.as_ref().and_then(|t| t.last())
+    }
+
+    /// Wrap the draws in an "egui" debug group with a marker per texture batch so they are easy to
+    /// find in RenderDoc or Xcode captures. On by default in debug builds, turn it off to skip the
+    /// string formatting.
+    pub fn set_debug_labels(&mut self, enabled: bool) {
+        self.debug_labels = enabled;
+    }
+
+    /// Whether the draws are labelled, see [`EguiPipe::set_debug_labels`]
+    pub fn debug_labels(&self) -> bool {
+        self.debug_labels
+    }
+
+    /// Draw groups whose texture hasn't been uploaded yet with `id` instead of skipping them, e.g. a
+    /// "loading" checkerboard registered through egui. `id` must itself be a texture egui has sent
+    /// to `prepare`.
+    pub fn with_fallback_texture(mut self, id: TextureId) -> Self {
+        self.fallback_texture = Some(id);
+        self
+    }
+
+    /// Set or clear the fallback texture, see [`EguiPipe::with_fallback_texture`]
+    pub fn set_fallback_texture(&mut self, id: Option<TextureId>) {
+        self.fallback_texture = id;
+        self.bundles = None;
+    }
+
+    /// Sample the backdrop `blur` blurred last for meshes textured with [`blur::BLUR_TEXTURE_ID`],
+    /// e.g. a [`blur::backdrop_shape`] painted under a translucent window. Call it every frame
+    /// after [`blur::BlurPass::blur`] as the blurred texture is recreated when the source resizes.
+    #[cfg(feature = "blur")]
+    pub fn set_blur_backdrop(&mut self, paint: &Painter, blur: &blur::BlurPass) {
+        self.blur_backdrop = blur.blurred().map(|tex| {
+            paint.binding_group(
+                self.texture_layout(),
+                &[tex, &self.blit_sampler],
+                Some("Egui blur backdrop binding group"),
+            )
+        });
+        self.bundles = None;
+    }
+
+    /// Stop sampling a blurred backdrop, see [`EguiPipe::set_blur_backdrop`]
+    #[cfg(feature = "blur")]
+    pub fn clear_blur_backdrop(&mut self) {
+        self.blur_backdrop = None;
+        self.bundles = None;
+    }
+
+    /// Call `hook` with the id and proposed format of every egui texture created from now on, and
+    /// create it with the format returned, e.g. `Rgba8Unorm` for one image holding linear data.
+    /// Textures are uploaded as 8 bit RGBA, so other formats fail with [`EguiError::UnsupportedFormat`].
+    pub fn set_texture_format_hook(
+        &mut self,
+        hook: impl Fn(TextureId, wgpu::TextureFormat) -> wgpu::TextureFormat + Send + Sync + 'static,
+    ) {
+        self.texture_format_hook = FormatHook(Some(Box::new(hook)));
+    }
+
+    /// Create textures with the proposed format again
+    pub fn clear_texture_format_hook(&mut self) {
+        self.texture_format_hook = FormatHook::default();
+    }
+
+    /// How texture `id` is sampled outside of 0 to 1 in u and v, e.g. [`wgpu::AddressMode::Repeat`]
+    /// to tile an image with [`egui::widgets::Image::uv`]. Textures clamp to the edge by default.
+    /// egui 0.18 doesn't carry sampling options with its textures, so they are set here instead.
+    pub fn set_texture_wrap(
+        &mut self,
+        paint: &Painter,
+        id: TextureId,
+        wrap: [wgpu::AddressMode; 2],
+    ) {
+        self.texture_wrap.insert(id, wrap);
+        if let Some((tex, _)) = self.egui_texture.remove(&id) {
+            let binding = self.texture_binding(paint, id, &tex);
+            self.egui_texture.insert(id, (tex, binding));
+            self.bundles = None;
+        }
+    }
+
+    /// Create the binding group of every texture again, e.g. after replacing [`EguiPipe::sampler`]
+    /// to change the filter mode. Bindings hold the sampler they were made with, so only textures
+    /// uploaded afterwards would pick up the new one otherwise.
+    pub fn rebuild_texture_bindings(&mut self, paint: &Painter) {
+        let textures: Vec<_> = self
+            .egui_texture
+            .iter()
+            .map(|(id, (tex, _))| (*id, tex.clone()))
+            .collect();
+        for (id, tex) in textures {
+            let binding = self.texture_binding(paint, id, &tex);
+            self.egui_texture.insert(id, (tex, binding));
+        }
+        self.bundles = None;
+    }
+
+    /// Draw `view` of a texture made by another library, wrapped by neither parrot nor egui, for
+    /// meshes with the returned id, e.g. `egui::Image::new(id, size)`. The texture needs
+    /// `TEXTURE_BINDING` usage and a filterable float format, like the 8 bit RGBA and BGRA ones,
+    /// holding premultiplied alpha; an sRGB format if the pipe's [`ColorSpace`] creates sRGB
+    /// textures, so colors aren't decoded twice or not at all. `view` must be a single 2D layer
+    /// and is sampled with the [`EguiPipe::sampler`] of the moment.
+    ///
+    /// Ids count up from `TextureId::User(0)`, skipping ones already taken, so don't mix them with
+    /// user ids of another source. The pipe keeps the view alive until
+    /// [`EguiPipe::unregister_wgpu_texture`]. What's rendered into the texture shows without
+    /// registering it again, but a texture created anew has to be, as do all of them after
+    /// [`EguiPipe::recreate`].
+    pub fn register_wgpu_texture(
+        &mut self,
+        paint: &Painter,
+        view: &wgpu::TextureView,
+    ) -> TextureId {
+        let mut id = TextureId::User(self.next_wgpu_texture);
+        while self.texture_id_taken(id) {
+            self.next_wgpu_texture += 1;
+            id = TextureId::User(self.next_wgpu_texture);
+        }
+        self.next_wgpu_texture += 1;
+        self.insert_wgpu_texture(paint, id, view);
+        id
+    }
+
+    /// Draw a parrot `texture` made by the app, e.g. a render target, for meshes with the returned
+    /// id. The same as [`EguiPipe::register_wgpu_texture`] with the texture's view, with the same
+    /// requirements, and it's unregistered with [`EguiPipe::unregister_wgpu_texture`] too.
+    pub fn register_native_texture(&mut self, paint: &Painter, texture: &Texture) -> TextureId {
+        self.register_wgpu_texture(paint, &texture.view)
+    }
+
+    /// Like [`EguiPipe::register_native_texture`], but draw `texture` for meshes with a chosen
+    /// `id`, e.g. to keep ids the same across runs when they're serialized or replayed.
+    ///
+    /// Errors with [`EguiError::TextureIdTaken`] rather than shadow a texture already under `id`,
+    /// whether registered here or uploaded by egui, or when `id` is reserved: the
+    /// [`FONT_ATLAS_ID`] egui draws text with, the cursor's and the blur backdrop's.
+    pub fn register_native_texture_with_id(
+        &mut self,
+        paint: &Painter,
+        id: TextureId,
+        texture: &Texture,
+    ) -> Result<(), EguiError> {
+        if self.texture_id_taken(id) {
+            return Err(EguiError::TextureIdTaken(id));
+        }
+        self.insert_wgpu_texture(paint, id, &texture.view);
+        Ok(())
+    }
+
+    // Whether meshes with `id` already draw some texture, or will once the pipe uses the id
+    fn texture_id_taken(&self, id: TextureId) -> bool {
+        #[cfg(feature = "blur")]
+        if id == blur::BLUR_TEXTURE_ID {
+            return true;
+        }
+        id == FONT_ATLAS_ID
+            || id == CURSOR_TEXTURE_ID
+            || self.wgpu_textures.contains_key(&id)
+            || self.egui_texture.contains_key(&id)
+    }
+
+    // Bind `view` for meshes with `id`
+    fn insert_wgpu_texture(&mut self, paint: &Painter, id: TextureId, view: &wgpu::TextureView) {
+        let layout = self.texture_layout();
+        let bind_group = paint
+            .device
+            .wgpu
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(format!("Egui texture {:?} binding group", id).as_str()),
+                layout: &layout.wgpu,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler.wgpu),
+                    },
+                ],
+            });
+        self.wgpu_textures
+            .insert(id, BindingGroup::new(layout.set_index, bind_group));
+        self.bundles = None;
+    }
+
+    /// Stop drawing a texture from [`EguiPipe::register_wgpu_texture`], letting its view go.
+    /// Returns false if `id` wasn't registered.
+    pub fn unregister_wgpu_texture(&mut self, id: TextureId) -> bool {
+        self.bundles = None;
+        self.wgpu_textures.remove(&id).is_some()
+    }
+
+    /// Create every GPU object of the pipe again on `paint`'s device, e.g. after the driver reset
+    /// and the old device was lost. Options are kept; pipelines, buffers, samplers and bind groups
+    /// are rebuilt, and the textures are uploaded again from the copies kept by
+    /// [`EguiPipe::set_retain_textures`].
+    ///
+    /// Without those copies the pipe starts with no textures, and egui believes it already sent
+    /// them. Call `ctx.set_fonts` with the fonts in use, [`egui::FontDefinitions::default`]
+    /// otherwise, for egui to send the font atlas again, and load the app's own textures again.
+    /// A shader from [`EguiPipe::reload_shader`], a [`EguiPipe::set_blur_backdrop`] and the
+    /// paint callbacks' resources have to be set up again too. Pipes sharing a
+    /// [`TextureStore`] are all recreated, the one uploading the textures first.
+    pub fn recreate(&mut self, paint: &Painter) {
+        let fresh = EguiPipeBuilder {
+            color_space: self.color_space,
+            static_screen: self.static_screen,
+            extra_texture_usages: self.extra_texture_usages,
+            index_format: self.index_format,
+            coverage_mask: self.coverage_mask,
+            conservative_rasterization: self.conservative_rasterization,
+        }
+        .build(paint);
+        self.vertex_buffer = fresh.vertex_buffer;
+        self.index_buffer = fresh.index_buffer;
+        self.chunks.clear();
+        self.sampler = fresh.sampler;
+        self.blit_sampler = fresh.blit_sampler;
+        self.core = fresh.core;
+        self.shader = fresh.shader;
+        self.gpu_timer = fresh.gpu_timer;
+        self.sample_count = fresh.sample_count;
+
+        // Everything else is created again on first use
+        self.samplers.clear();
+        self.egui_texture.clear();
+        self.wgpu_textures.clear();
+        self.layer_pipeline = None;
+        self.overdraw_pipeline = None;
+        self.prepass_pipelines = None;
+        self.stencil_pipeline = None;
+        self.subpixel_pipelines = None;
+        self.scaled_target = None;
+        self.blit_pipeline = None;
+        self.composite_pipeline = None;
+        self.bundles = None;
+        #[cfg(feature = "blur")]
+        {
+            self.blur_backdrop = None;
+        }
+        self.prev_vertices.clear();
+        self.uniform_dirty = true;
+        self.cursor_dirty = true;
+
+        if let Some(retained) = self.retained_textures.take() {
+            self.retained_textures = Some(RetainedTextures(HashMap::new()));
+            for (id, image) in retained.0 {
+                self.upload_texture(paint, id, ImageDelta::full(image));
+            }
+        }
+        // Take the store's textures again once they are uploaded to the new device
+        if let Some((store, seen)) = &mut self.texture_store {
+            if let Ok(store) = store.lock() {
+                *seen = store.generation.wrapping_sub(1);
+            }
+        }
+    }
+
+    /// The texture drawn in place of missing textures, if any
+    pub fn fallback_texture(&self) -> Option<TextureId> {
+        self.fallback_texture
+    }
+
+    /// Prepare like `paint.update_pipeline` does, taking the primitives as they come instead of in
+    /// a `Vec`, e.g. straight out of a custom tessellator. Uploads the uniform itself.
+    pub fn prepare_from_iter(
+        &mut self,
+        paint: &mut Painter,
+        textures_delta: egui::TexturesDelta,
+        primitives: impl IntoIterator<Item = egui::ClippedPrimitive>,
+        screen: ScreenDescriptor,
+    ) {
+        if self.prepare_frame(paint, textures_delta, primitives, screen) {
+            paint
+                .device
+                .update_buffer(&[self.uniform], &mut self.core.uniforms[0]);
+        }
+    }
+
+    /// Render the prepared UI on its own into `target`, which is first cleared to transparent black.
+    ///
+    /// The result is premultiplied: the color channels hold color * coverage and alpha holds the
+    /// coverage, so anti-aliased edges aren't darkened. Composite it over your image with
+    /// `out.rgb = layer.rgb + dst.rgb * (1 - layer.a)` (blend factors `One` and `OneMinusSrcAlpha`).
+    ///
+    /// It's also what a compositor expects from a transparent window in premultiplied alpha mode,
+    /// which `render` into a pass cleared to transparent black gives as well, and [`selftest()`]
+    /// checks the alpha of uncovered, translucent and opaque pixels. wgpu 0.13 configures every
+    /// surface with an opaque composite alpha mode though, so for an overlay window draw into a
+    /// layer and hand it to the windowing system some other way.
+    ///
+    /// `target` must be single sampled, usable as a render attachment and the size given by the
+    /// [`ScreenDescriptor`] passed to `prepare`. Use an sRGB format to get the same colors as on screen.
+    pub fn render_layer(&mut self, paint: &mut Painter, target: &Texture) {
+        let mut encoder = paint.device.create_command_encoder();
+        self.begin_gpu_timer(&mut encoder);
+        self.record_layer(paint, &mut encoder, target);
+        self.end_gpu_timer(&mut encoder);
+        paint.device.submit(vec![encoder.finish()]);
+    }
+
+    /// Begin a pass on `encoder` into `view`, loading or clearing it by `load_op`, render the
+    /// prepared UI as [`Render::render`] does and end the pass. For driving a raw encoder rather
+    /// than a pass of your own; use `render` to draw into a pass shared with other drawing.
+    /// `view` must match [`EguiPipe::target_format`] and [`EguiPipe::sample_count`] and have no
+    /// depth attachment, so use `render` for multisampled targets that need resolving.
+    ///
+    /// [`Render::render`]: pigeon_2d::pipeline::Render::render
+    pub fn encode(
+        &mut self,
+        paint: &mut Painter,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        load_op: PassOp,
+    ) {
+        self.begin_gpu_timer(encoder);
+        {
+            let mut pass = wgpu::RenderPass::begin(encoder, view, None, None, load_op);
+            pigeon_2d::pipeline::Render::render(self, paint, &mut pass);
+        }
+        self.end_gpu_timer(encoder);
+    }
+
+    /// Render at [`ScreenDescriptor::render_scale`] times the window size into an offscreen target,
+    /// then filter it down (or up) onto `target`, which must be single sampled and window sized.
+    pub fn render_scaled(&mut self, paint: &mut Painter, target: &wgpu::TextureView) {
+        self.update_offscreen_target(paint);
+        if self.blit_pipeline.is_none() {
+            self.blit_pipeline = Some(self.blit_pipeline(paint, 1));
+        }
+
+        // Take the target out so the layer can borrow the pipe
+        let scaled = self.scaled_target.take();
+        if let Some((tex, binding)) = &scaled {
+            self.render_layer(paint, tex);
+
+            let mut encoder = paint.device.create_command_encoder();
+            if let Some(blit) = &self.blit_pipeline {
+                self.record_blit(&mut encoder, blit, binding, target, None);
+            }
+            paint.device.submit(vec![encoder.finish()]);
+        }
+        self.scaled_target = scaled;
+    }
+
+    /// Render single sampled into an offscreen target, then composite it into `target` with
+    /// `sample_count` samples, resolving into `resolve_target` if given. For drawing over a
+    /// multisampled scene when the pipe was set up with a different sample count than the
+    /// scene's pass. `target` must be window sized and in the pipe's target format.
+    pub fn render_composited(
+        &mut self,
+        paint: &mut Painter,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+        sample_count: u32,
+    ) {
+        self.update_offscreen_target(paint);
+        if self.composite_pipeline.as_ref().map(|p| p.0) != Some(sample_count) {
+            let pipeline = self.blit_pipeline(paint, sample_count);
+            self.composite_pipeline = Some((sample_count, pipeline));
+        }
+
+        let offscreen = self.scaled_target.take();
+        if let Some((tex, binding)) = &offscreen {
+            self.begin_gpu_timer(encoder);
+            self.record_layer(paint, encoder, tex);
+            self.end_gpu_timer(encoder);
+            if let Some((_, composite)) = &self.composite_pipeline {
+                self.record_blit(encoder, composite, binding, target, resolve_target);
+            }
+        }
+        self.scaled_target = offscreen;
+    }
+
+    // Clear `target` and draw the UI into it with the layer pipeline
+    fn record_layer(
+        &mut self,
+        paint: &Painter,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Texture,
+    ) {
+        if self.layer_pipeline.as_ref().map(|p| p.0) != Some(target.format) {
+            let pipeline = self.variant_pipeline(
+                paint,
+                PipelineVariant {
+                    conservative: self.conservative_rasterization,
+                    ..PipelineVariant::new(target.format, 1)
+                },
+                Some("Egui layer pipe"),
+            );
+            self.layer_pipeline = Some((target.format, pipeline));
+        }
+        if let Some((_, pipeline)) = &self.layer_pipeline {
+            let mut pass = wgpu::RenderPass::begin(
+                encoder,
+                &target.view,
+                None,
+                None,
+                PassOp::Clear(Rgba::TRANSPARENT),
+            );
+            self.draw(paint, &mut pass, pipeline, None, None);
+        }
+    }
+
+    // (Re)create the offscreen target at the render size
+    fn update_offscreen_target(&mut self, paint: &Painter) {
+        if self.scaled_target.as_ref().map(|t| t.0.size) != Some(self.render_size) {
+            let tex = paint.texture(
+                self.render_size,
+                self.color_space.target_format(),
+                wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                Some("Egui scaled target"),
+                false,
+            );
+            let binding = paint.binding_group(
+                self.texture_layout(),
+                &[&tex, &self.blit_sampler],
+                Some("Egui scaled target binding group"),
+            );
+            self.scaled_target = Some((tex, binding));
+        }
+    }
+
+    fn blit_pipeline(&self, paint: &Painter, sample_count: u32) -> wgpu::RenderPipeline {
+        let variant = PipelineVariant {
+            vs_entry: "vs_fullscreen",
+            fs_entry: "fs_blit",
+            vertex_buffer: false,
+            ..PipelineVariant::new(self.color_space.target_format(), sample_count)
+        };
+        self.variant_pipeline(paint, variant, Some("Egui blit pipe"))
+    }
+
+    // Composite an offscreen target over `target`
+    fn record_blit(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::RenderPipeline,
+        source: &BindingGroup,
+        target: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+    ) {
+        let mut pass =
+            wgpu::RenderPass::begin(encoder, target, resolve_target, None, PassOp::Load());
+        pass.set_pipeline(pipeline);
+        pass.set_binding(&self.bindings[0], &[]);
+        pass.set_binding(source, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    // Create or update the texture for `id` on the GPU
+    fn upload_texture(&mut self, paint: &Painter, id: TextureId, delta: ImageDelta) {
+        if let Err(e) = self.try_upload_texture(paint, id, delta) {
+            log::error!("Failed to upload egui texture >> {}", e);
+            self.push_error(e);
+        }
+    }
+
+    fn try_upload_texture(
+        &mut self,
+        paint: &Painter,
+        id: TextureId,
+        delta: ImageDelta,
+    ) -> Result<(), EguiError> {
+        // Function to get color from image delta
+        let set_data = |d: ImageDelta| -> Vec<egui::Color32> {
+            match d {
+                ImageDelta {
+                    image: ImageData::Color(c),
+                    ..
+                } => c.pixels,
+                ImageDelta {
+                    image: ImageData::Font(f),
+                    ..
+                } => f.srgba_pixels(1f32).collect::<Vec<egui::Color32>>(),
+            }
+        };
+
+        let size: Size2D<u32, ScreenSpace> =
+            Size2D::new(delta.image.width() as u32, delta.image.height() as u32);
+        let existing = self.egui_texture.get(&id).map(|t| t.0.size);
+        let pos = delta.pos;
+        let b = set_data(delta);
+        if let Some(pos) = pos {
+            // Fill part of the texture
+            let t = self
+                .egui_texture
+                .get(&id)
+                .ok_or(EguiError::UnknownTexture(id))?;
+            let texels = texels(&b, t.0.format);
+            let data = Rgba8::align(texels.as_slice());
+            Texture::transfer(
+                &t.0,
+                data,
+                Rect::new(Point2D::new(pos[0] as u32, pos[1] as u32), size),
+                &paint.device,
+            );
+        } else {
+            // Fill whole texture, creating it first if it's new or changed size
+            if existing != Some(size) {
+                self.create_texture(paint, id, size)?;
+            }
+            let tex = &self.egui_texture[&id].0;
+            let texels = texels(&b, tex.format);
+            let data = Rgba8::align(texels.as_slice());
+            Texture::fill(tex, data, &paint.device);
+        }
+
+        // Keep what the texture now holds for recreate
+        if let Some(RetainedTextures(retained)) = &mut self.retained_textures {
+            match pos {
+                Some(pos) => {
+                    if let Some(image) = retained.get_mut(&id) {
+                        blit(
+                            &mut image.pixels,
+                            image.size[0],
+                            &b,
+                            size.width as usize,
+                            pos,
+                        );
+                    }
+                }
+                None => {
+                    let size = [size.width as usize, size.height as usize];
+                    retained.insert(id, egui::ColorImage { size, pixels: b });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn create_texture(
+        &mut self,
+        paint: &Painter,
+        id: TextureId,
+        size: Size2D<u32, ScreenSpace>,
+    ) -> Result<(), EguiError> {
+        let max = max_texture_side(paint);
+        if size.width > max || size.height > max {
+            return Err(EguiError::TextureTooLarge {
+                id,
+                size: [size.width, size.height],
+                max,
+            });
+        }
+        let format = self.color_space.texture_format();
+        let format = match &self.texture_format_hook.0 {
+            Some(hook) => hook(id, format),
+            None => format,
+        };
+        if !matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            return Err(EguiError::UnsupportedFormat { id, format });
+        }
+        let tex = paint.texture(
+            size,
+            format,
+            // COPY_SRC for read_texture
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | self.extra_texture_usages,
+            Some(format!("Egui texture {:?}", id).as_str()),
+            false,
+        );
+        if !self.egui_texture.contains_key(&id) {
+            self.evict_textures();
+        }
+        let tex = Arc::new(tex);
+        let binding = self.texture_binding(paint, id, &tex);
+        self.egui_texture.insert(id, (tex.clone(), binding));
+        // Bundles only know the bind groups that existed when they were recorded
+        self.bundles = None;
+        if let Some((store, seen)) = &mut self.texture_store {
+            if let Ok(mut store) = store.lock() {
+                store.textures.insert(id, tex);
+                store.generation += 1;
+                *seen = store.generation;
+            }
+        }
+        Ok(())
+    }
+
+    // Drop texture `id`, also from the shared store
+    fn remove_texture(&mut self, id: TextureId) {
+        self.egui_texture.remove(&id);
+        if let Ok(used) = self.texture_last_used.get_mut() {
+            used.remove(&id);
+        }
+        if let Some(RetainedTextures(retained)) = &mut self.retained_textures {
+            retained.remove(&id);
+        }
+        self.bundles = None;
+        if let Some((store, seen)) = &mut self.texture_store {
+            if let Ok(mut store) = store.lock() {
+                if store.textures.remove(&id).is_some() {
+                    store.generation += 1;
+                    *seen = store.generation;
+                }
+            }
+        }
+    }
+
+    // Make room for a new texture under max_textures by removing the least recently drawn ones
+    fn evict_textures(&mut self) {
+        let max = match self.max_textures {
+            Some(max) => max,
+            None => return,
+        };
+        while self.egui_texture.len() >= max {
+            let used = self.texture_last_used.lock().ok();
+            let oldest = self
+                .egui_texture
+                .keys()
+                .filter(|id| **id != FONT_ATLAS_ID && **id != CURSOR_TEXTURE_ID)
+                .filter(|id| !self.pinned_textures.contains(id))
+                .min_by_key(|id| used.as_ref().and_then(|u| u.get(id).copied()).unwrap_or(0))
+                .copied();
+            drop(used);
+            match oldest {
+                Some(id) => {
+                    log::debug!(
+                        "Evicting the least recently used egui texture >> Id: {:?}",
+                        id
+                    );
+                    self.remove_texture(id);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Pick up textures other pipes added to or removed from the shared store
+    fn sync_texture_store(&mut self, paint: &Painter) {
+        let store = match &self.texture_store {
+            Some((store, seen)) => match store.lock() {
+                Ok(store) if store.generation != *seen => store.clone(),
+                _ => return,
+            },
+            None => return,
+        };
+        self.egui_texture
+            .retain(|id, _| store.textures.contains_key(id));
+        for (id, tex) in &store.textures {
+            let current = self.egui_texture.get(id).map(|t| &t.0);
+            if !current.is_some_and(|t| Arc::ptr_eq(t, tex)) {
+                let binding = self.texture_binding(paint, *id, tex);
+                self.egui_texture.insert(*id, (tex.clone(), binding));
+            }
+        }
+        self.bundles = None;
+        if let Some((_, seen)) = &mut self.texture_store {
+            *seen = store.generation;
+        }
+    }
+
+    // Bind group for texture `id` with the sampler for its wrap mode
+    fn texture_binding(&mut self, paint: &Painter, id: TextureId, tex: &Texture) -> BindingGroup {
+        let wrap = self.texture_wrap.get(&id).copied();
+        let sampler = match wrap.and_then(sampler_key) {
+            None => &self.sampler,
+            Some(key) => self.samplers.entry(key).or_insert_with(|| Sampler {
+                wgpu: paint.device.wgpu.create_sampler(&wgpu::SamplerDescriptor {
+                    label: Some("Egui texture sampler"),
+                    address_mode_u: key.2,
+                    address_mode_v: key.3,
+                    mag_filter: key.0,
+                    min_filter: key.1,
+                    ..Default::default()
+                }),
+            }),
+        };
+        paint.binding_group(
+            &self.core.pipeline.layout.b_layouts[TEXTURE_SET],
+            &[tex, sampler],
+            Some(format!("Egui texture {:?} binding group", id).as_str()),
+        )
+    }
+
+    fn free_texture(&mut self, id: TextureId) {
+        if self.pinned_textures.contains(&id) {
+            log::warn!("Egui freed a pinned texture, keeping it >> Id: {:?}", id);
+            return;
+        }
+        self.remove_texture(id);
+        self.pending_textures.0.retain(|(i, _)| *i != id);
+    }
+
+    // Queue a texture change while uploads are paused, merging it with those already queued for `id`
+    fn queue_texture(&mut self, id: TextureId, delta: ImageDelta) {
+        match delta.pos {
+            // A whole image replaces everything queued before it
+            None => self.pending_textures.0.retain(|(i, _)| *i != id),
+            Some(pos) => {
+                // Patch the last queued whole image instead of queueing another change
+                let full = self
+                    .pending_textures
+                    .0
+                    .iter_mut()
+                    .rev()
+                    .find(|(i, d)| *i == id && d.pos.is_none());
+                if let Some((_, full)) = full {
+                    match (&mut full.image, &delta.image) {
+                        (ImageData::Color(dst), ImageData::Color(src)) => {
+                            blit(&mut dst.pixels, dst.size[0], &src.pixels, src.size[0], pos);
+                            return;
+                        }
+                        (ImageData::Font(dst), ImageData::Font(src)) => {
+                            blit(&mut dst.pixels, dst.size[0], &src.pixels, src.size[0], pos);
+                            return;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        self.pending_textures.0.push((id, delta));
+    }
+
+    // Upload the vertices, writing only runs of changed colors if the geometry matches last frame
+    fn update_vertices(&mut self, paint: &mut Painter, vertices: &[Vertex]) {
+        let stride = std::mem::size_of::<Vertex>();
+        // The color follows the position and uv, see VERTEX_LAYOUT
+        let color_offset = 2 * std::mem::size_of::<egui::Pos2>();
+        let bytes: &[u8] = bytemuck::cast_slice(vertices);
+
+        let same_geometry = vertices.len() == self.prev_vertices.len()
+            && vertices
+                .iter()
+                .zip(&self.prev_vertices)
+                .all(|(v, p)| v.pos == p.pos && v.uv == p.uv);
+        if same_geometry {
+            self.vertex_upload_bytes = 0;
+            let changed = |i: usize| vertices[i].color != self.prev_vertices[i].color;
+            let mut i = 0;
+            while i < vertices.len() {
+                if !changed(i) {
+                    i += 1;
+                    continue;
+                }
+                // Write from the first changed color to the end of the last one in the run
+                let start = i;
+                while i < vertices.len() && changed(i) {
+                    i += 1;
+                }
+                let range = start * stride + color_offset..i * stride;
+                paint.device.queue.write_buffer(
+                    &self.vertex_buffer.wgpu,
+                    range.start as wgpu::BufferAddress,
+                    &bytes[range.clone()],
+                );
+                self.vertex_upload_bytes += range.len();
+            }
+        } else {
+            if let Some(v) = paint.update_vertex_buffer(vertices, &mut self.vertex_buffer) {
+                self.vertex_buffer = v;
+                log::debug!(
+                    "Grew the egui vertex buffer >> Capacity: {}",
+                    self.buffer_capacities().0
+                );
+            }
+            self.vertex_upload_bytes = bytes.len();
+        }
+
+        self.prev_vertices.clear();
+        self.prev_vertices.extend_from_slice(vertices);
+    }
+
+    // Upload the indices with 16 bit indices if preferred and they can reach every vertex
+    fn update_indices(&mut self, paint: &mut Painter, indices: Vec<u32>, vertex_count: usize) {
+        let capacity = self.buffer_capacities().1;
+        let wide =
+            self.index_format == wgpu::IndexFormat::Uint32 || vertex_count > u16::MAX as usize + 1;
+        let name = Some("Egui index buffer");
+        match (&mut self.index_buffer, wide) {
+            (EguiIndexBuffer::U32(buffer), true) => {
+                if let Some(i) = paint.update_index_buffer_32(indices, buffer) {
+                    *buffer = i;
+                }
+            }
+            (EguiIndexBuffer::U16(buffer), false) => {
+                let indices = indices.into_iter().map(|i| i as u16).collect();
+                if let Some(i) = paint.update_index_buffer(indices, buffer) {
+                    *buffer = i;
+                }
+            }
+            // The width changed since last frame
+            (_, true) => {
+                self.index_buffer = EguiIndexBuffer::U32(paint.index_buffer_32(&indices, name))
+            }
+            (_, false) => {
+                let indices: Vec<u16> = indices.into_iter().map(|i| i as u16).collect();
+                self.index_buffer = EguiIndexBuffer::U16(paint.index_buffer(&indices, name));
+            }
+        }
+        if self.buffer_capacities().1 != capacity {
+            log::debug!(
+                "Resized the egui index buffer >> Capacity: {}",
+                self.buffer_capacities().1
+            );
+        }
+    }
+
+    // Replace the buffers with empty ones when the shrink policy says so, for the update to
+    // reallocate them to fit
+    fn shrink_buffers(&mut self, paint: &Painter, vertex_count: usize, index_count: usize) {
+        let (fraction, frames) = match self.shrink_policy {
+            ShrinkPolicy::Never => return,
+            ShrinkPolicy::WhenUnderused { fraction, frames } => (fraction, frames),
+        };
+        let (vertex_capacity, index_capacity) = self.buffer_capacities();
+        let underused = (vertex_count as f32) < vertex_capacity as f32 * fraction
+            && (index_count as f32) < index_capacity as f32 * fraction;
+        self.underused_frames = match underused {
+            true => self.underused_frames + 1,
+            false => 0,
+        };
+        if self.underused_frames < frames {
+            return;
+        }
+        self.underused_frames = 0;
+        log::debug!(
+            "Shrinking the egui buffers >> Capacity: {:?}, Used: {:?}",
+            (vertex_capacity, index_capacity),
+            (vertex_count, index_count)
+        );
+        self.vertex_buffer = paint.vertex_buffer::<Vertex>(&[], Some("Egui vertex buffer"));
+        // Write every vertex into the new buffer rather than only changed colors
+        self.prev_vertices.clear();
+        let name = Some("Egui index buffer");
+        self.index_buffer = match self.index_buffer {
+            EguiIndexBuffer::U16(_) => EguiIndexBuffer::U16(paint.index_buffer(&[], name)),
+            EguiIndexBuffer::U32(_) => EguiIndexBuffer::U32(paint.index_buffer_32(&[], name)),
+        };
+        self.bundles = None;
+    }
+
+    // Create the subpixel text pipelines if they don't match the sample count, see set_subpixel_text
+    fn update_subpixel_pipelines(&mut self, paint: &Painter) {
+        let sample_count = paint.sample_count();
+        if self.subpixel_mode() == 0 {
+            self.subpixel_pipelines = None;
+        } else if self.subpixel_pipelines.as_ref().map(|p| p.0) != Some(sample_count) {
+            let variant = |fs_entry, blend| PipelineVariant {
+                fs_entry,
+                blend,
+                ..self.pass_variant(paint)
+            };
+            let mask = self.variant_pipeline(
+                paint,
+                variant("fs_subpixel_mask", SUBPIXEL_MASK_BLENDING),
+                Some("Egui subpixel mask pipe"),
+            );
+            let color = self.variant_pipeline(
+                paint,
+                variant("fs_subpixel_color", SUBPIXEL_COLOR_BLENDING),
+                Some("Egui subpixel color pipe"),
+            );
+            self.subpixel_pipelines = Some((sample_count, (mask, color)));
+        }
+    }
+
+    // Create the depth prepass pipelines if they don't match its format
+    fn update_prepass_pipelines(&mut self, paint: &Painter) {
+        let format = match self.depth_prepass {
+            Some(prepass) => prepass.format,
+            None => {
+                self.prepass_pipelines = None;
+                return;
+            }
+        };
+        if self.prepass_pipelines.as_ref().map(|p| p.0) == Some(format) {
+            return;
+        }
+        let variant = |write: bool, blend| PipelineVariant {
+            vs_entry: "vs_layered",
+            blend,
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: write,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            ..self.pass_variant(paint)
+        };
+        let opaque = self.variant_pipeline(
+            paint,
+            variant(true, wgpu::BlendState::REPLACE),
+            Some("Egui opaque pipe"),
+        );
+        let blended = self.variant_pipeline(
+            paint,
+            variant(false, PREMULTIPLIED_BLENDING),
+            Some("Egui blended pipe"),
+        );
+        self.prepass_pipelines = Some((format, opaque, blended));
+    }
+
+    // The pipeline variant for the pass given to render, with the coverage target if enabled
+    fn pass_variant(&self, paint: &Painter) -> PipelineVariant<'static> {
+        let variant = PipelineVariant {
+            conservative: self.conservative_rasterization,
+            ..PipelineVariant::new(self.color_space.target_format(), paint.sample_count())
+        };
+        if self.coverage_mask {
+            PipelineVariant {
+                fs_entry: "fs_coverage",
+                coverage: true,
+                ..variant
+            }
+        } else {
+            variant
+        }
+    }
+
+    // Entry points the pipe builds pipelines with, now or once an option needs them
+    fn entry_points(&self) -> Vec<(&'static str, naga::ShaderStage)> {
+        use naga::ShaderStage::{Fragment, Vertex};
+        let mut entries = vec![
+            (VS_ENTRY, Vertex),
+            ("vs_layered", Vertex),
+            ("vs_fullscreen", Vertex),
+            (FS_ENTRY, Fragment),
+            ("fs_blit", Fragment),
+            ("fs_overdraw", Fragment),
+        ];
+        if self.coverage_mask {
+            entries.push(("fs_coverage", Fragment));
+        }
+        if cfg!(feature = "subpixel") {
+            entries.push(("fs_subpixel_mask", Fragment));
+            entries.push(("fs_subpixel_color", Fragment));
+        }
+        entries
+    }
+
+    // Layout of the texture and sampler set, which `setup` makes sure the pipeline has
+    fn texture_layout(&self) -> &parrot::binding::BindingGroupLayout {
+        &self.core.pipeline.layout.b_layouts[TEXTURE_SET]
+    }
+
+    // Build a variant of the pipeline sharing its layout and shader
+    fn variant_pipeline(
+        &self,
+        paint: &Painter,
+        variant: PipelineVariant<'_>,
+        name: Option<&str>,
+    ) -> wgpu::RenderPipeline {
+        egui_render_pipeline(
+            &paint.device,
+            &self.core.pipeline.layout,
+            &self.core.pipeline.vertex_layout,
+            &self.shader,
+            variant,
+            name,
+        )
+    }
+
+    // Texture binding for a group, falling back to the placeholder. None if the group is skipped
+    fn group_binding(&self, group: &Group) -> Option<&BindingGroup> {
+        #[cfg(feature = "blur")]
+        if group.tex_id == blur::BLUR_TEXTURE_ID {
+            return self.blur_backdrop.as_ref();
+        }
+        if let Some(binding) = self.wgpu_textures.get(&group.tex_id) {
+            return Some(binding);
+        }
+        self.egui_texture
+            .get(&group.tex_id)
+            .or_else(|| {
+                self.fallback_texture
+                    .and_then(|id| self.egui_texture.get(&id))
+            })
+            .map(|t| &t.1)
+    }
+
+    // Record the groups into a bundle per run sharing a scissor rect, as bundles can't set scissors
+    fn record_bundles(&self, paint: &Painter) -> (Vec<Bundle>, RenderStats) {
+        let mut stats = RenderStats::default();
+        let mut runs: Vec<BundleRun> = vec![];
+        for group in &self.groups {
+            match self.group_binding(group) {
+                Some(binding) if !group.pixel_rect.is_empty() => match runs.last_mut() {
+                    Some((rect, run)) if *rect == group.pixel_rect => run.push((group, binding)),
+                    _ => runs.push((group.pixel_rect, vec![(group, binding)])),
+                },
+                _ => stats.skipped_groups += 1,
+            }
+        }
+        let bundles = runs
+            .into_iter()
+            .map(|(rect, run)| (rect, self.encode_bundle(paint, &run, &mut stats)))
+            .collect();
+        (bundles, stats)
+    }
+
+    // Encode the draws of `run` into a bundle of its own
+    fn encode_bundle(
+        &self,
+        paint: &Painter,
+        run: &[(&Group, &BindingGroup)],
+        stats: &mut RenderStats,
+    ) -> wgpu::RenderBundle {
+        // The coverage mask is the second target when enabled
+        let formats = [
+            Some(self.color_space.target_format()),
+            Some(COVERAGE_FORMAT),
+        ];
+        let mut encoder =
+            paint
+                .device
+                .wgpu
+                .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                    label: Some("Egui bundle"),
+                    color_formats: &formats[..1 + self.coverage_mask as usize],
+                    depth_stencil: None,
+                    sample_count: paint.sample_count(),
+                    multiview: None,
+                });
+        encoder.set_pipeline(&self.core.pipeline.wgpu);
+        encoder.set_parrot_vertex_buffer(&self.vertex_buffer);
+        encoder.set_index_buffer(self.index_buffer.slice(), self.index_buffer.format());
+        encoder.set_binding(&self.bindings[0], &[]);
+        for (group, binding) in run {
+            encoder.set_binding(binding, &[]);
+            encoder.draw_parrot_indexed(group.range.clone(), 0..1);
+            stats.binding_changes += 1;
+            stats.draw_calls += 1;
+        }
+        stats.scissor_changes += 1;
+        encoder.finish(&wgpu::RenderBundleDescriptor {
+            label: Some("Egui bundle"),
+        })
+    }
+
+    // Draw a callback in its viewport and clip rect, then reset the viewport
+    fn draw_callback<'a>(
+        &'a self,
+        paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        cb: &'a PreparedCallback,
+    ) {
+        let rect = cb.pixel_rect;
+        if rect.is_empty() {
+            return;
+        }
+        let v = cb.viewport;
+        pass.set_viewport(v.origin.x, v.origin.y, v.width(), v.height(), 0.0, 1.0);
+        pass.set_scissor_rect(rect.origin.x, rect.origin.y, rect.width(), rect.height());
+        cb.callback
+            .paint(&cb.info, paint, pass, &self.callback_resources);
+        self.set_ui_viewport(pass);
+    }
+
+    // Record the draws for the prepared groups using `pipeline`
+    // With `opaque`, only the groups with that opacity are drawn, the opaque ones front to back.
+    // Each group is drawn as its own instance for vs_layered.
+    // Glyph runs are drawn with `subpixel` if given, see set_subpixel_text
+    fn draw<'a>(
+        &'a self,
+        paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        opaque: Option<bool>,
+        subpixel: Option<&'a SubpixelPipelines>,
+    ) {
+        let groups = 0..self.groups.len();
+        self.draw_range(paint, pass, pipeline, opaque, subpixel, groups);
+    }
+
+    // Draw only the groups in `groups`, with the callbacks before them. The outlines and the
+    // callbacks after the last group go with the range ending there.
+    fn draw_range<'a>(
+        &'a self,
+        paint: &Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        opaque: Option<bool>,
+        subpixel: Option<&'a SubpixelPipelines>,
+        groups: std::ops::Range<usize>,
+    ) {
+        scope!("egui_pigeon::draw");
+        let last = groups.end == self.groups.len();
+        let wanted = |g: &Group| opaque.is_none() || opaque == Some(g.opaque);
+        let outlines = self
+            .clip_rects_range
+            .as_ref()
+            .filter(|r| !r.is_empty() && opaque != Some(true) && last);
+        // Callbacks are drawn in order with the blended groups
+        let callbacks: &[PreparedCallback] = match opaque {
+            Some(true) => &[],
+            _ => {
+                let start = self
+                    .callbacks
+                    .partition_point(|c| c.before_group < groups.start);
+                let end = self
+                    .callbacks
+                    .partition_point(|c| c.before_group < groups.end || last);
+                &self.callbacks[start..end.max(start)]
+            }
+        };
+        // Record nothing at all if every group would be skipped
+        let drawable = outlines.is_some()
+            || !callbacks.is_empty()
+            || self.groups[groups.clone()]
+                .iter()
+                .any(|g| wanted(g) && !g.pixel_rect.is_empty() && self.group_binding(g).is_some());
+        if !drawable {
+            self.record_stats(RenderStats {
+                skipped_groups: groups.len() as u32,
+                ..Default::default()
+            });
+            return;
+        }
+
+        if self.debug_labels {
+            pass.push_debug_group("egui");
+        }
+
+        if self.destination.is_some() {
+            self.set_ui_viewport(pass);
+        }
+
+        // Set pipeline and the buffers of a chunk, again after each callback
+        let bind = |pass: &mut wgpu::RenderPass<'a>, chunk: usize| {
+            pass.set_pipeline(pipeline);
+            match chunk.checked_sub(1).and_then(|c| self.chunks.get(c)) {
+                Some((vertices, indices)) => {
+                    pass.set_parrot_vertex_buffer(vertices);
+                    pass.set_index_buffer(indices.slice(), wgpu::IndexFormat::Uint32);
+                }
+                // Empty until a frame has a mesh, and wgpu can't bind an empty buffer
+                None if self.vertex_buffer.size == 0 => (),
+                None => {
+                    pass.set_parrot_vertex_buffer(&self.vertex_buffer);
+                    pass.set_index_buffer(self.index_buffer.slice(), self.index_buffer.format());
+                }
+            }
+            pass.set_binding(&self.bindings[0], &[]);
+        };
+        let mut chunk = 0;
+        bind(pass, chunk);
+
+        let mut stats = RenderStats::default();
+        let mut drawn = vec![];
+        let mut batch = None;
+        let mut order: Vec<usize> = groups.collect();
+        if opaque == Some(true) {
+            order.reverse();
+        }
+        let mut callbacks = callbacks.iter().peekable();
+        for i in order {
+            while let Some(cb) = callbacks.next_if(|c| c.before_group <= i) {
+                self.draw_callback(paint, pass, cb);
+                bind(pass, chunk);
+            }
+            let group = &self.groups[i];
+            if !wanted(group) {
+                continue;
+            }
+            if group.chunk != chunk {
+                chunk = group.chunk;
+                bind(pass, chunk);
+            }
+            if group.pixel_rect.is_empty() {
+                stats.skipped_groups += 1;
+            } else {
+                // Mark where each run of groups with the same texture starts
+                if self.debug_labels && batch != Some(group.tex_id) {
+                    pass.insert_debug_marker(&format!("egui texture {:?}", group.tex_id));
+                    batch = Some(group.tex_id);
+                }
+
+                if let Some(binding) = self.group_binding(group) {
+                    pass.set_binding(binding, &[]);
+                    stats.binding_changes += 1;
+                    drawn.push(group.tex_id);
+                } else {
+                    log::warn!("Unknown texture >> {:?}", group.tex_id);
+                    stats.skipped_groups += 1;
+                    continue;
+                }
+
+                // Set scissor rect, a transformed UI isn't clipped
+                if self.transform.is_none() {
+                    pass.set_scissor_rect(
+                        group.pixel_rect.origin.x,
+                        group.pixel_rect.origin.y,
+                        group.pixel_rect.width(),
+                        group.pixel_rect.height(),
+                    );
+                }
+                let layer = i as u32;
+                match subpixel.filter(|_| group.text) {
+                    Some((mask, color)) => {
+                        pass.set_pipeline(mask);
+                        pass.draw_parrot_indexed(group.range.clone(), layer..layer + 1);
+                        pass.set_pipeline(color);
+                        pass.draw_parrot_indexed(group.range.clone(), layer..layer + 1);
+                        pass.set_pipeline(pipeline);
+                        stats.draw_calls += 2;
+                    }
+                    None => {
+                        pass.draw_parrot_indexed(group.range.clone(), layer..layer + 1);
+                        stats.draw_calls += 1;
+                    }
+                }
+                stats.scissor_changes += 1;
+            }
+        }
+
+        for cb in callbacks {
+            self.draw_callback(paint, pass, cb);
+            bind(pass, chunk);
+        }
+
+        // Outlines are drawn with the font atlas' white texel over the whole target, above the groups
+        if let (Some(range), Some(font)) = (outlines, self.egui_texture.get(&FONT_ATLAS_ID)) {
+            let layer = self.groups.len() as u32;
+            pass.set_binding(&font.1, &[]);
+            if self.transform.is_none() {
+                let rect = self.target_rect();
+                pass.set_scissor_rect(rect.origin.x, rect.origin.y, rect.width(), rect.height());
+            }
+            pass.draw_parrot_indexed(range.clone(), layer..layer + 1);
+            stats.binding_changes += 1;
+            stats.scissor_changes += 1;
+            stats.draw_calls += 1;
+        }
+
+        if self.debug_labels {
+            pass.pop_debug_group();
+        }
+        self.mark_drawn(drawn);
+        self.record_stats(stats);
+    }
+
+    // Everything `prepare` does but hand over the uniform. True if it has to be uploaded.
+    fn prepare_frame(
+        &mut self,
+        paint: &mut Painter,
+        textures: egui::TexturesDelta,
+        primitives: impl IntoIterator<Item = egui::ClippedPrimitive>,
+        mut screen: ScreenDescriptor,
+    ) -> bool {
+        scope!("egui_pigeon::prepare");
+        // Collect timestamps from previous frames
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.poll(&paint.device);
+        }
+        // render_range borrows the pipe for the pass, so can't create pipelines itself
+        self.update_subpixel_pipelines(paint);
+
+        self.sync_texture_store(paint);
+
+        // The frame that used the textures egui freed last time has been rendered
+        for id in std::mem::take(&mut self.pending_free) {
+            self.free_texture(id);
+        }
+
+        let mut vertices: Vec<egui::epaint::Vertex> = vec![];
+        let mut indices: Vec<u32> = vec![];
+        let mut groups: Vec<Group> = vec![];
+        let mut callbacks: Vec<PreparedCallback> = vec![];
+
+        screen = screen.with_ui_scale(self.ui_scale);
+        screen.render_scale *= self.render_scale;
+        // A broken descriptor gives NaN positions and scissors, so draw nothing until it's fixed
+        let max_size = max_texture_side(paint);
+        let valid = match screen.validate(max_size) {
+            Ok(()) => {
+                self.warned_screen = None;
+                true
+            }
+            // Minimizing is expected, not worth a warning
+            Err(_) if screen.is_minimized() => {
+                log::debug!("Skipping the egui frame of a minimized window");
+                false
+            }
+            Err(e) => {
+                if self.warned_screen.as_ref() != Some(&e) {
+                    log::error!("Skipping the egui frame >> {}", e);
+                }
+                self.push_error(e.clone());
+                self.warned_screen = Some(e);
+                false
+            }
+        };
+
+        let pixels_per_point = screen.scissor_scale();
+        let size_in_pixels = screen.render_size_in_pixels();
+        let rotation = self.rotation;
+        if valid {
+            let [width, height] = rotation.rotate_size(size_in_pixels);
+            self.render_size = Size2D::new(width, height);
+        }
+        let subpixel = self.subpixel_mode() != 0;
+        let window = screen.window_clip_rect();
+        let scissor_offset = match self.displace_scissors {
+            true => self.displacement,
+            false => egui::Vec2::ZERO,
+        };
+        let zoom = self.ui_zoom;
+        let scissor =
+            |clip: egui::Rect| zoom_rect(clip, window.center(), zoom).translate(scissor_offset);
+
+        // The backdrop goes below everything else, staying put when the UI is displaced or zoomed
+        if let Some(color) = self.background.filter(|_| valid) {
+            let screen_rect = screen.screen_rect();
+            let backdrop = zoom_rect(
+                screen_rect.translate(-self.displacement),
+                screen_rect.center(),
+                1.0 / zoom,
+            );
+            let mut mesh = egui::epaint::Mesh::default();
+            mesh.add_colored_rect(backdrop, color);
+            groups.push(Group {
+                range: 0..mesh.indices.len() as u32,
+                tex_id: FONT_ATLAS_ID,
+                pixel_rect: Rect::from_size(self.render_size),
+                bounds: mesh.calc_bounds(),
+                opaque: self.depth_prepass.is_some() && color.a() == 255,
+                text: false,
+                chunk: 0,
+                primitive: None,
+            });
+            indices.append(&mut mesh.indices);
+            vertices.append(&mut mesh.vertices);
+        }
+        // Clip rects past the edge of the screen mean egui tessellated at a smaller scale than given
+        let mut implied = screen.scale_factors();
+        let mut mismatched = false;
+        for (index, mut primative) in valid
+            .then_some(primitives)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            // NaN edges would give garbage scissors
+            if primative.clip_rect.any_nan() {
+                if !self.warned_nan_clip {
+                    log::warn!("Egui gave a NaN clip rect >> {:?}", primative.clip_rect);
+                    self.warned_nan_clip = true;
+                }
+                match self.nan_clip {
+                    NanClip::FullScreen => primative.clip_rect = window,
+                    NanClip::Skip => continue,
+                }
+            }
+            if screen.clip_space == ClipSpace::Logical {
+                mismatched |= imply_pixels_per_point(
+                    &mut implied,
+                    &primative.clip_rect,
+                    screen.size_in_pixels,
+                );
+            }
+            match primative.primitive {
+                egui::epaint::Primitive::Callback(_) if self.transform.is_some() => {
+                    log::debug!("Skipping a paint callback in a transformed UI");
+                }
+                egui::epaint::Primitive::Callback(cb) => {
+                    let info = egui::epaint::PaintCallbackInfo {
+                        viewport: cb.rect,
+                        clip_rect: primative.clip_rect,
+                        pixels_per_point: screen.pixels_per_point,
+                        screen_size_px: size_in_pixels,
+                    };
+                    let mut slot = CallbackSlot::default();
+                    cb.call(&info, &mut slot);
+                    let callback = match slot.0 {
+                        Some(c) => c,
+                        None => {
+                            log::warn!(
+                                "Skipping a paint callback >> {}",
+                                EguiError::CallbackMismatch
+                            );
+                            self.push_error(EguiError::CallbackMismatch);
+                            continue;
+                        }
+                    };
+                    callback.prepare(paint, &info, &mut self.callback_resources);
+                    let [x, y] = screen.scale_factors();
+                    let (x, y) = (x * screen.render_scale, y * screen.render_scale);
+                    let target = Rect::from_size(self.render_size.to_f32());
+                    let rect = zoom_rect(cb.rect, screen.screen_rect().center(), zoom)
+                        .translate(self.displacement);
+                    let viewport = Rect::new(
+                        Point2D::new(rect.min.x * x, rect.min.y * y),
+                        Size2D::new(rect.width() * x, rect.height() * y),
+                    );
+                    let viewport = rotation
+                        .rotate_rect(viewport, size_in_pixels.map(|s| s as f32))
+                        .intersection(&target);
+                    let pixel_rect = rotation.rotate_pixel_rect(
+                        calculate_pixel_rect(
+                            &scissor(primative.clip_rect),
+                            pixels_per_point,
+                            size_in_pixels,
+                            self.clip_rounding,
+                        ),
+                        size_in_pixels,
+                    );
+                    if let Some(viewport) = viewport.filter(|v| !v.is_empty()) {
+                        callbacks.push(PreparedCallback {
+                            before_group: groups.len(),
+                            info,
+                            viewport,
+                            pixel_rect,
+                            callback,
+                        });
+                    }
+                }
+                egui::epaint::Primitive::Mesh(mut mesh) => {
+                    let si = indices.len() as u32;
+                    let mut offset = match offset_indices(&mesh.indices, vertices.len()) {
+                        Ok(offset) => offset,
+                        Err(e) => {
+                            log::error!("Skipping an egui mesh >> {}", e);
+                            self.push_error(e);
+                            continue;
+                        }
+                    };
+
+                    // Get clipping rect
+                    let pixel_rect = screen.scissor_rect(
+                        &primative.clip_rect,
+                        &scissor(primative.clip_rect),
+                        self.clip_rounding,
+                    );
+                    let pixel_rect = rotation.rotate_pixel_rect(pixel_rect, size_in_pixels);
+                    // Runs as (indices, opaque, text)
+                    let runs: Vec<_> = match self.depth_prepass {
+                        Some(_) => opaque_runs(&mesh)
+                            .into_iter()
+                            .map(|(range, opaque)| (range, opaque, false))
+                            .collect(),
+                        None if subpixel && mesh.texture_id == FONT_ATLAS_ID => text_runs(&mesh)
+                            .into_iter()
+                            .map(|(range, text)| (range, false, text))
+                            .collect(),
+                        None => vec![(0..mesh.indices.len() as u32, false, false)],
+                    };
+                    if self.debug_mode == DebugMode::TextureTint {
+                        let tint = texture_tint(mesh.texture_id);
+                        for v in &mut mesh.vertices {
+                            v.color = multiply_color(v.color, tint);
+                        }
+                    }
+                    let bounds = mesh.calc_bounds();
+                    indices.append(&mut offset);
+                    vertices.append(&mut mesh.vertices);
+                    for (range, opaque, text) in runs {
+                        groups.push(Group {
+                            range: si + range.start..si + range.end,
+                            tex_id: mesh.texture_id,
+                            pixel_rect,
+                            bounds,
+                            opaque,
+                            text,
+                            chunk: 0,
+                            primitive: Some(index),
+                        });
+                    }
+                }
+            }
+        }
+
+        let implied = mismatched.then_some(implied);
+        if implied.is_some() && implied != self.warned_pixels_per_point {
+            log::warn!(
+                "Clip rects don't fit the screen, the ScreenDescriptor scale may not match the one egui tessellated with >> Given: {:?}, Implied: {:?}",
+                screen.scale_factors(),
+                implied
+            );
+        }
+        self.warned_pixels_per_point = implied;
+
+        // The software cursor goes above everything else
+        let cursor = match (&self.software_cursor, self.cursor) {
+            (Some(sheet), Some((pos, icon))) => sheet.mesh(icon, pos),
+            _ => None,
+        };
+        if let Some(mut mesh) = cursor {
+            match offset_indices(&mesh.indices, vertices.len()) {
+                Ok(mut offset) => {
+                    let si = indices.len() as u32;
+                    let bounds = mesh.calc_bounds();
+                    indices.append(&mut offset);
+                    vertices.append(&mut mesh.vertices);
+                    groups.push(Group {
+                        range: si..indices.len() as u32,
+                        tex_id: CURSOR_TEXTURE_ID,
+                        pixel_rect: Rect::from_size(self.render_size),
+                        bounds,
+                        opaque: false,
+                        text: false,
+                        chunk: 0,
+                        primitive: None,
+                    });
+                }
+                Err(e) => {
+                    log::error!("Skipping the software cursor >> {}", e);
+                    self.push_error(e);
+                }
+            }
+        }
+
+        // Outline every distinct clip rect
+        self.clip_rects_range = None;
+        if self.debug_mode == DebugMode::ClipRects {
+            let mut outlines = egui::epaint::Mesh::default();
+            let mut seen = HashSet::new();
+            for group in &groups {
+                if seen.insert(group.pixel_rect) {
+                    // The outline is turned with the rest of the UI
+                    let target = [self.render_size.width, self.render_size.height];
+                    add_outline(
+                        &mut outlines,
+                        rotation
+                            .inverse()
+                            .rotate_pixel_rect(group.pixel_rect, target),
+                        pixels_per_point,
+                        texture_color(group.tex_id),
+                    );
+                }
+            }
+            match offset_indices(&outlines.indices, vertices.len()) {
+                Ok(mut offset) => {
+                    let si = indices.len() as u32;
+                    indices.append(&mut offset);
+                    vertices.append(&mut outlines.vertices);
+                    self.clip_rects_range = Some(si..indices.len() as u32);
+                }
+                Err(e) => {
+                    log::error!("Skipping the clip rect outlines >> {}", e);
+                    self.push_error(e);
+                }
+            }
+        }
+
+        // Mirror the scissors with the UI, after the outlines are drawn around them
+        if self.flip_y {
+            let height = self.render_size.height;
+            for group in &mut groups {
+                group.pixel_rect.origin.y = height - group.pixel_rect.max_y();
+            }
+            for cb in &mut callbacks {
+                cb.pixel_rect.origin.y = height - cb.pixel_rect.max_y();
+                cb.viewport.origin.y = height as f32 - cb.viewport.max_y();
+            }
+        }
+
+        // Move the scissors into the destination, after the outlines are drawn around them
+        if let Some(dest) = self.destination {
+            let offset = dest.origin.to_vector();
+            let inside = |r: Rect<u32, ScreenSpace>| {
+                r.translate(offset).intersection(&dest).unwrap_or_default()
+            };
+            for group in &mut groups {
+                group.pixel_rect = inside(group.pixel_rect);
+            }
+            for cb in &mut callbacks {
+                cb.pixel_rect = inside(cb.pixel_rect);
+                cb.viewport = cb.viewport.translate(offset.to_f32());
+            }
+        }
+
+        // Split a frame past the device's buffer limit rather than have wgpu panic creating the buffer
+        let max = paint.device.wgpu.limits().max_buffer_size;
+        let needed = (std::mem::size_of_val(vertices.as_slice()) as u64)
+            .max(std::mem::size_of_val(indices.as_slice()) as u64);
+        let mut chunks = vec![];
+        if needed > max {
+            let mut skipped = vec![];
+            chunks = split_chunks(&mut vertices, &mut indices, &mut groups, max, &mut skipped);
+            for e in skipped {
+                self.push_error(e);
+            }
+            // The outlines index the geometry before it was split
+            self.clip_rects_range = None;
+        }
+        // Nothing, not even the cursor, is drawn with an invalid descriptor
+        if !valid {
+            vertices.clear();
+            indices.clear();
+            groups.clear();
+            chunks.clear();
+        }
+
+        // Record the bundles again if anything they draw changed
+        if self.render_bundles {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            bytemuck::cast_slice::<_, u8>(&vertices).hash(&mut hasher);
+            indices.hash(&mut hasher);
+            for group in &groups {
+                (group.range.clone(), group.tex_id, group.pixel_rect).hash(&mut hasher);
+            }
+            let hash = hasher.finish();
+            if hash != self.draw_hash {
+                self.bundles = None;
+            }
+            self.draw_hash = hash;
+        }
+
+        // Update buffers. Parrot writes into the existing buffer when the data fits and returns None,
+        // otherwise it returns a new buffer that has already been filled. Neither path can fail
+        // short of a wgpu validation error, which panics in wgpu itself.
+        {
+            scope!("egui_pigeon::update_buffers");
+            self.shrink_buffers(paint, vertices.len(), indices.len());
+            self.update_vertices(paint, &vertices);
+            self.update_indices(paint, indices, vertices.len());
+            self.chunks = chunks
+                .into_iter()
+                .map(|(vertices, indices)| {
+                    (
+                        paint.vertex_buffer(&vertices, Some("Egui vertex buffer chunk")),
+                        paint.index_buffer_32(&indices, Some("Egui index buffer chunk")),
+                    )
+                })
+                .collect();
+        }
+        self.groups = groups;
+        self.callbacks = callbacks;
+        self.texture_frame += 1;
+        self.reset_stats();
+
+        // Apply what was queued while uploads were paused before this frame's changes
+        {
+            scope!("egui_pigeon::upload_textures");
+            if self.uploads_enabled {
+                for (id, delta) in std::mem::take(&mut self.pending_textures.0) {
+                    self.upload_texture(paint, id, delta);
+                }
+            }
+            for (id, delta) in textures.set {
+                if self.uploads_enabled {
+                    self.upload_texture(paint, id, delta);
+                } else {
+                    self.queue_texture(id, delta);
+                }
+            }
+        }
+        self.pending_free = textures.free;
+
+        if self.cursor_dirty {
+            self.cursor_dirty = false;
+            match &self.software_cursor {
+                Some(sheet) => {
+                    let delta = ImageDelta::full(sheet.image.clone());
+                    self.upload_texture(paint, CURSOR_TEXTURE_ID, delta);
+                }
+                None => self.remove_texture(CURSOR_TEXTURE_ID),
+            }
+        }
+
+        // The uniform would be built from the invalid sizes, keep the last one
+        if !valid {
+            return false;
+        }
+
+        // Create the uniform, kept for upload_uniforms
+        let uniform = Uniform {
+            // Only the depth prepass spreads the groups over depths
+            max_depth: self.depth_prepass.map_or(0.0, |p| p.max_depth),
+            layers: match self.depth_prepass {
+                Some(_) => self.groups.len() as u32,
+                None => 0,
+            },
+            pixel_snap: self.pixel_snapping as u32,
+            feather: self.feather,
+            screen_size_in_pixels: size_in_pixels.map(|s| s as f32),
+            rotation: match self.rotation.matrix() {
+                // Negate y after turning
+                [[a, b], [c, d]] if self.flip_y => [[a, -b], [c, -d]],
+                matrix => matrix,
+            },
+            transformed: self.transform.is_some() as u32,
+            subpixel: self.subpixel_mode(),
+            displacement: [self.displacement.x, self.displacement.y],
+            zoom: self.ui_zoom,
+            time: self.time.unwrap_or_else(|| {
+                (self.created.elapsed().as_secs_f64() % TIME_WRAP as f64) as f32
+            }),
+            transform: match self.transform {
+                Some(t) => t.model.then(&t.projection).to_arrays(),
+                None => Transform3D::<f32, ScreenSpace, ScreenSpace>::identity().to_arrays(),
+            },
+            ui_tint: match self.color_space {
+                // Blending happens in gamma space, so the tint has to be too
+                ColorSpace::LinearTarget => egui::Color32::from(self.tint)
+                    .to_array()
+                    .map(|c| c as f32 / 255.0),
+                ColorSpace::SrgbTarget => self.tint.to_array(),
+            },
+            ..Uniform::new(
+                self.shader_settings,
+                screen.screen_size_in_points(),
+                self.color_space,
+            )
+        };
+        // Depths follow the number of groups
+        if self.depth_prepass.is_some()
+            && (uniform.max_depth, uniform.layers) != (self.uniform.max_depth, self.uniform.layers)
+        {
+            self.uniform_dirty = true;
+        }
+        self.uniform = uniform;
+
+        // Static screens keep the last uniform until invalidated
+        if self.static_screen && !self.uniform_dirty {
+            return false;
+        }
+        self.uniform_dirty = false;
+        true
+    }
+}
+
+impl<'a> Plumber<'a> for EguiPipe {
+    type PrepareContext = (
+        egui::TexturesDelta,
+        Vec<egui::ClippedPrimitive>,
+        ScreenDescriptor,
+    );
+    type Uniforms = Uniform;
+
+    fn description() -> parrot::pipeline::PipelineDescription<'a> {
+        PipelineDescription {
+            vertex_layout: &VERTEX_LAYOUT,
+            pipeline_layout: Some(&[
+                Set(
+                    &[Binding {
+                        binding: BindingType::UniformBuffer,
+                        stage: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    }],
+                    Some("Egui screen bind group"),
+                ),
+                Set(
+                    &[
+                        Binding {
+                            binding: BindingType::Texture {
+                                multisampled: false,
+                            },
+                            stage: wgpu::ShaderStages::FRAGMENT,
+                        },
+                        Binding {
+                            binding: BindingType::Sampler,
+                            stage: wgpu::ShaderStages::FRAGMENT,
+                        },
+                    ],
+                    Some("Egui texture bind group"),
+                ),
+            ]),
+            shader: parrot::shader::ShaderFile::Wgsl(SHADER),
+            name: Some("Egui pipe"),
+        }
+    }
+
+    // `container` is only kept for the apps still setting it
+    #[allow(deprecated)]
+    fn setup(pipe: parrot::pipeline::Pipeline, paint: &parrot::Painter) -> Self {
+        if let Err(e) = check_layout(&pipe.layout) {
+            // A pipeline from `Painter::custom_pipeline` with other sets, the pipe's own works
+            log::error!("Replacing the egui pipeline >> {}", e);
+            let mut pipe: Self = paint.pipeline_no_depth(
+                Blending::default(),
+                ColorSpace::default().target_format(),
+                Some("Egui shader"),
+            );
+            pipe.push_error(e);
+            return pipe;
+        }
+        let vertex_buffer =
+            paint.vertex_buffer::<egui::epaint::Vertex>(&[], Some("Egui vertex buffer"));
+        let index_buffer =
+            EguiIndexBuffer::U32(paint.index_buffer_32(&[], Some("Egui index buffer")));
+        let egui_texture = HashMap::new();
+        let uniform = Uniform::new(ShaderSettings::default(), [1.0, 1.0], ColorSpace::default());
+        let uniform_buffer = paint.uniform_buffer(&[uniform], Some("Egui uniform buffer"));
+        let sampler = paint.sampler(
+            wgpu::FilterMode::Nearest,
+            wgpu::FilterMode::Linear,
+            Some("Egui texture sampler"),
+        );
+        let binding = paint.binding_group(
+            &pipe.layout.b_layouts[UNIFORM_SET],
+            &[&uniform_buffer],
+            Some("Egui uniform binding group"),
+        );
+        let core = PipelineCore {
+            pipeline: pipe,
+            bindings: vec![binding],
+            uniforms: vec![uniform_buffer],
+        };
+        let container = None;
+        let blit_sampler = paint.sampler(
+            wgpu::FilterMode::Linear,
+            wgpu::FilterMode::Linear,
+            Some("Egui blit sampler"),
+        );
+        let shader = paint
+            .device
+            .create_wgsl_shader(SHADER, Some("Egui shader"))
+            .wgpu;
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_format: wgpu::IndexFormat::Uint32,
+            egui_texture,
+            texture_store: None,
+            groups: vec![],
+            sampler,
+            samplers: HashMap::new(),
+            texture_wrap: HashMap::new(),
+            container,
+            callbacks: vec![],
+            chunks: vec![],
+            callback_resources: CallbackResources::default(),
+            color_space: ColorSpace::default(),
+            sample_count: paint.sample_count(),
+            debug_mode: DebugMode::default(),
+            clip_rounding: ClipRounding::default(),
+            nan_clip: NanClip::default(),
+            warned_nan_clip: false,
+            static_screen: false,
+            uniform_dirty: true,
+            uniform,
+            shader_settings: ShaderSettings::default(),
+            tint: egui::Rgba::WHITE,
+            pixel_snapping: false,
+            feather: 1.0,
+            rotation: ScreenRotation::None,
+            transform: None,
+            flip_y: false,
+            displacement: egui::Vec2::ZERO,
+            displace_scissors: true,
+            background: None,
+            destination: None,
+            time: None,
+            created: Instant::now(),
+            #[cfg(feature = "subpixel")]
+            subpixel_text: None,
+            subpixel_pipelines: None,
+            extra_texture_usages: wgpu::TextureUsages::empty(),
+            texture_format_hook: FormatHook::default(),
+            uploads_enabled: true,
+            pending_textures: PendingTextures::default(),
+            pending_free: vec![],
+            pinned_textures: HashSet::new(),
+            max_textures: None,
+            wgpu_textures: HashMap::new(),
+            next_wgpu_texture: 0,
+            retained_textures: None,
+            texture_frame: 0,
+            texture_last_used: Mutex::default(),
+            ui_scale: 1.0,
+            ui_zoom: 1.0,
+            render_scale: 1.0,
+            render_bundles: false,
+            shrink_policy: ShrinkPolicy::default(),
+            underused_frames: 0,
+            bundles: None,
+            draw_hash: 0,
+            prev_vertices: vec![],
+            vertex_upload_bytes: 0,
+            render_stats: Mutex::default(),
+            draw_call_budget: None,
+            over_budget: AtomicBool::new(false),
+            gpu_timer: GpuTimer::new(&paint.device),
+            debug_labels: cfg!(debug_assertions),
+            coverage_mask: false,
+            conservative_rasterization: false,
+            fallback_texture: None,
+            #[cfg(feature = "blur")]
+            blur_backdrop: None,
+            software_cursor: None,
+            cursor_dirty: false,
+            cursor: None,
+            core,
+            shader,
+            layer_pipeline: None,
+            overdraw_pipeline: None,
+            stencil_mask: None,
+            stencil_pipeline: None,
+            depth_prepass: None,
+            prepass_pipelines: None,
+            clip_rects_range: None,
+            warned_pixels_per_point: None,
+            warned_screen: None,
+            errors: vec![],
+            render_size: Size2D::default(),
+            scaled_target: None,
+            blit_pipeline: None,
+            composite_pipeline: None,
+            blit_sampler,
+        }
+    }
+
+    fn prepare(
+        &'a mut self,
+        context: Self::PrepareContext,
+        paint: &mut parrot::Painter,
+    ) -> Vec<(&'a mut UniformBuffer, Vec<Self::Uniforms>)> {
+        if self.prepare_frame(paint, context.0, context.1, context.2) {
+            vec![(&mut self.core.uniforms[0], vec![self.uniform])]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl pigeon_2d::pipeline::Render for EguiPipe {
+    type Vertex = Vertex;
+
+    fn render<'a>(&'a mut self, paint: &mut parrot::Painter, pass: &mut wgpu::RenderPass<'a>) {
+        scope!("egui_pigeon::render");
+        // A changed sample count fails the pass in wgpu with no mention of the pipe
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.validate_target(self.target_format(), paint.sample_count()) {
+                if !self.errors.contains(&e) {
+                    log::error!("Egui can't render into the pass >> {}", e);
+                }
+                self.push_error(e);
+            }
+        }
+        // The overdraw shader has no coverage output
+        if self.debug_mode == DebugMode::Overdraw
+            && self.overdraw_pipeline.is_none()
+            && !self.coverage_mask
+        {
+            let variant = PipelineVariant {
+                fs_entry: "fs_overdraw",
+                blend: ADDITIVE_BLENDING,
+                conservative: self.conservative_rasterization,
+                ..PipelineVariant::new(self.color_space.target_format(), paint.sample_count())
+            };
+            self.overdraw_pipeline =
+                Some(self.variant_pipeline(paint, variant, Some("Egui overdraw pipe")));
+        }
+
+        if let Some(mask) = self.stencil_mask {
+            let key = self.stencil_pipeline.as_ref().map(|p| (p.0, p.1));
+            if key != Some((mask.format, mask.compare)) {
+                let face = wgpu::StencilFaceState {
+                    compare: mask.compare,
+                    ..Default::default()
+                };
+                let variant = PipelineVariant {
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: mask.format,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil: wgpu::StencilState {
+                            front: face,
+                            back: face,
+                            read_mask: !0,
+                            write_mask: 0,
+                        },
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    ..self.pass_variant(paint)
+                };
+                let pipeline = self.variant_pipeline(paint, variant, Some("Egui stencil pipe"));
+                self.stencil_pipeline = Some((mask.format, mask.compare, pipeline));
+            }
+        }
+
+        self.update_prepass_pipelines(paint);
+
+        self.update_subpixel_pipelines(paint);
+
+        let bundled = self.render_bundles
+            && self.debug_mode == DebugMode::Off
+            && self.stencil_mask.is_none()
+            && self.depth_prepass.is_none()
+            && self.transform.is_none()
+            && self.subpixel_pipelines.is_none()
+            && self.callbacks.is_empty()
+            && self.chunks.is_empty();
+        if bundled && self.bundles.is_none() {
+            self.bundles = Some(self.record_bundles(paint));
+        }
+
+        let pipe: &'a Self = self;
+        if let (Some(mask), Some((_, _, stencil))) = (pipe.stencil_mask, &pipe.stencil_pipeline) {
+            pass.set_stencil_reference(mask.reference);
+            pipe.draw(paint, pass, stencil, None, None);
+            return;
+        }
+        if let Some((_, _, blended)) = &pipe.prepass_pipelines {
+            pipe.draw(paint, pass, blended, Some(false), None);
+            return;
+        }
+        if let (true, Some((bundles, stats))) = (bundled, &pipe.bundles) {
+            if pipe.destination.is_some() {
+                pipe.set_ui_viewport(pass);
+            }
+            for (rect, bundle) in bundles {
+                pass.set_scissor_rect(rect.origin.x, rect.origin.y, rect.width(), rect.height());
+                pass.execute_bundles(std::iter::once(bundle));
+            }
+            pipe.mark_drawn(pipe.groups.iter().map(|g| g.tex_id));
+            pipe.record_stats(*stats);
+            return;
+        }
+        match (pipe.debug_mode, &pipe.overdraw_pipeline) {
+            (DebugMode::Overdraw, Some(overdraw)) => pipe.draw(paint, pass, overdraw, None, None),
+            _ => {
+                let subpixel = pipe.subpixel_pipelines.as_ref().map(|p| &p.1);
+                pipe.draw(paint, pass, &pipe.core.pipeline.wgpu, None, subpixel)
+            }
+        }
+    }
+}
+
+pub fn setup(paint: &Painter) -> EguiPipe {
+    setup_with_color_space(paint, ColorSpace::SrgbTarget)
+}
+
+/// Largest width or height of a texture on `paint`'s device, e.g. for `egui_winit::State::new` so
+/// egui never grows its font atlas past what the GPU can create
+pub fn max_texture_side(paint: &Painter) -> u32 {
+    paint.device.wgpu.limits().max_texture_dimension_2d
+}
+
+/// Create the pipe for a target in the given [`ColorSpace`]
+pub fn setup_with_color_space(paint: &Painter, color_space: ColorSpace) -> EguiPipe {
+    EguiPipeBuilder::new().color_space(color_space).build(paint)
+}
+
+/// Creates an [`EguiPipe`] with options that can't be changed afterwards
+#[derive(Debug, Clone)]
+pub struct EguiPipeBuilder {
+    color_space: ColorSpace,
+    static_screen: bool,
+    extra_texture_usages: wgpu::TextureUsages,
+    index_format: wgpu::IndexFormat,
+    coverage_mask: bool,
+    conservative_rasterization: bool,
+}
+
+impl Default for EguiPipeBuilder {
+    fn default() -> Self {
+        Self {
+            color_space: ColorSpace::default(),
+            static_screen: false,
+            extra_texture_usages: wgpu::TextureUsages::empty(),
+            index_format: wgpu::IndexFormat::Uint32,
+            coverage_mask: false,
+            conservative_rasterization: false,
+        }
+    }
+}
+
+impl EguiPipeBuilder {
+    /// Builder with the same options as [`setup`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color space of the render target
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Upload the uniform on the first `prepare` only, for windows that never change size. Call
+    /// [`EguiPipe::invalidate_uniform`] if they do.
+    pub fn static_screen(mut self, static_screen: bool) -> Self {
+        self.static_screen = static_screen;
+        self
+    }
+
+    /// Usages added to the `TEXTURE_BINDING | COPY_DST | COPY_SRC` every egui texture is created
+    /// with, e.g. `RENDER_ATTACHMENT` to draw into them
+    pub fn extra_texture_usages(mut self, usages: wgpu::TextureUsages) -> Self {
+        self.extra_texture_usages = usages;
+        self
+    }
+
+    /// Width of the index buffer. With `Uint16` frames with more vertices than 16 bit indices can
+    /// reach still use 32 bit indices.
+    pub fn index_format(mut self, format: wgpu::IndexFormat) -> Self {
+        self.index_format = format;
+        self
+    }
+
+    /// Also write where the UI drew and how opaquely into a second, [`COVERAGE_FORMAT`] target, e.g.
+    /// to keep post-processing off the UI. The pass given to `render` must then have that target as
+    /// its second color attachment, cleared to 0. [`DebugMode::Overdraw`] isn't available with it,
+    /// and [`EguiPipe::render_layer`] doesn't write the mask.
+    pub fn coverage_mask(mut self, coverage_mask: bool) -> Self {
+        self.coverage_mask = coverage_mask;
+        self
+    }
+
+    /// Rasterize every pixel the UI's triangles touch rather than those whose centre they cover,
+    /// so hairline separators and grid lines don't drop out at some scales. Edges come out up to a
+    /// pixel heavier. Needs [`wgpu::Features::CONSERVATIVE_RASTERIZATION`]; without it this is
+    /// logged and left off, or [`EguiPipeBuilder::try_build`] fails.
+    pub fn conservative_rasterization(mut self, conservative: bool) -> Self {
+        self.conservative_rasterization = conservative;
+        self
+    }
+
+    /// Create the pipe, failing with [`EguiError::MissingFeatures`] if the device lacks a feature
+    /// an option needs rather than leaving the option off like [`EguiPipeBuilder::build`]
+    pub fn try_build(self, paint: &Painter) -> Result<EguiPipe, EguiError> {
+        let conservative = wgpu::Features::CONSERVATIVE_RASTERIZATION;
+        if self.conservative_rasterization && !paint.device.wgpu.features().contains(conservative) {
+            return Err(EguiError::MissingFeatures(conservative));
+        }
+        Ok(self.build(paint))
+    }
+
+    /// Create the pipe. Options the device doesn't support are left off, with the reason in
+    /// [`EguiPipe::take_errors`].
+    pub fn build(self, paint: &Painter) -> EguiPipe {
+        let mut pipe: EguiPipe = paint.pipeline_no_depth(
+            Blending::default(),
+            self.color_space.target_format(),
+            Some("Egui shader"),
+        );
+        pipe.color_space = self.color_space;
+        pipe.static_screen = self.static_screen;
+        pipe.extra_texture_usages = self.extra_texture_usages;
+        pipe.index_format = self.index_format;
+        let conservative = wgpu::Features::CONSERVATIVE_RASTERIZATION;
+        if self.conservative_rasterization && !paint.device.wgpu.features().contains(conservative) {
+            log::warn!(
+                "Rasterizing egui normally >> {}",
+                EguiError::MissingFeatures(conservative)
+            );
+            pipe.push_error(EguiError::MissingFeatures(conservative));
+        } else {
+            pipe.conservative_rasterization = self.conservative_rasterization;
+        }
+        if self.coverage_mask || pipe.conservative_rasterization {
+            pipe.coverage_mask = self.coverage_mask;
+            pipe.core.pipeline.wgpu =
+                pipe.variant_pipeline(paint, pipe.pass_variant(paint), Some("Egui pipe"));
+        }
+        pipe
+    }
+}
+
+// Make sure a pipeline layout has the sets the pipe binds, each with the bindings it binds
+fn check_layout(layout: &PipelineLayout) -> Result<(), EguiError> {
+    for (index, purpose, size) in [(UNIFORM_SET, "uniform", 1), (TEXTURE_SET, "texture", 2)] {
+        match layout.b_layouts.get(index) {
+            Some(set) if set.size == size => (),
+            _ => return Err(EguiError::MissingBindGroupLayout { index, purpose }),
+        }
+    }
+    Ok(())
+}
+
+// Split the geometry into chunks whose vertices and indices each fit in `max` bytes, moving the
+// group ranges into their chunk. The first chunk is left in `vertices` and `indices` and the
+// others returned. Groups too large for a buffer of their own are skipped, with their errors added
+// to `errors`.
+fn split_chunks(
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    groups: &mut [Group],
+    max: u64,
+    errors: &mut Vec<EguiError>,
+) -> Vec<(Vec<Vertex>, Vec<u32>)> {
+    let vertex_size = std::mem::size_of::<Vertex>() as u64;
+    let index_size = std::mem::size_of::<u32>() as u64;
+    let mut chunks: Vec<(Vec<Vertex>, Vec<u32>)> = vec![Default::default()];
+    // Index of each vertex in the last chunk
+    let mut remap = HashMap::new();
+    for group in groups.iter_mut() {
+        let group_indices = &indices[group.range.start as usize..group.range.end as usize];
+        let distinct = group_indices.iter().collect::<HashSet<_>>().len() as u64;
+        let needed = (distinct * vertex_size).max(group_indices.len() as u64 * index_size);
+        if needed > max {
+            let e = EguiError::BufferResize { needed, max };
+            log::error!("Skipping an egui mesh >> {}", e);
+            errors.push(e);
+            group.range = 0..0;
+            group.pixel_rect = Rect::zero();
+            continue;
+        }
+
+        let (chunk_vertices, chunk_indices) = &chunks[chunks.len() - 1];
+        if (chunk_vertices.len() as u64 + distinct) * vertex_size > max
+            || (chunk_indices.len() + group_indices.len()) as u64 * index_size > max
+        {
+            chunks.push(Default::default());
+            remap.clear();
+        }
+        group.chunk = chunks.len() - 1;
+        let (chunk_vertices, chunk_indices) = &mut chunks[group.chunk];
+        let start = chunk_indices.len() as u32;
+        for &index in group_indices {
+            let local = *remap.entry(index).or_insert_with(|| {
+                chunk_vertices.push(vertices[index as usize]);
+                chunk_vertices.len() as u32 - 1
+            });
+            chunk_indices.push(local);
+        }
+        group.range = start..chunk_indices.len() as u32;
+    }
+    log::debug!(
+        "Split the egui geometry past the buffer limit >> Chunks: {}, Limit: {}",
+        chunks.len(),
+        max
+    );
+
+    let mut chunks = chunks.into_iter();
+    (*vertices, *indices) = chunks.next().unwrap_or_default();
+    chunks.collect()
+}
+
+// Indices of a mesh appended after `base` vertices, failing rather than wrapping past u32::MAX
+fn offset_indices(indices: &[u32], base: usize) -> Result<Vec<u32>, EguiError> {
+    let offset = u32::try_from(base)
+        .ok()
+        .and_then(|base| indices.iter().map(|&i| base.checked_add(i)).collect());
+    offset.ok_or_else(|| EguiError::TooManyVertices {
+        count: indices
+            .iter()
+            .max()
+            .map_or(base, |&i| base.saturating_add(i as usize + 1)),
+    })
+}
+
+// Split a mesh's indices into runs of opaque and blended triangles. Only solid triangles using the
+// font atlas' white texel are known to be opaque.
+fn opaque_runs(mesh: &egui::epaint::Mesh) -> Vec<(std::ops::Range<u32>, bool)> {
+    let font = mesh.texture_id == FONT_ATLAS_ID;
+    split_runs(mesh, |v| {
+        font && v.color.a() == 255 && v.uv == egui::epaint::WHITE_UV
+    })
+}
+
+// Split a font atlas mesh's indices into runs of glyphs and shapes. Glyphs sample the atlas away
+// from its white texel.
+fn text_runs(mesh: &egui::epaint::Mesh) -> Vec<(std::ops::Range<u32>, bool)> {
+    split_runs(mesh, |v| v.uv != egui::epaint::WHITE_UV)
+}
+
+// Split a mesh's indices into runs of triangles whose vertices all pass `test` and runs that don't
+fn split_runs(
+    mesh: &egui::epaint::Mesh,
+    test: impl Fn(&egui::epaint::Vertex) -> bool,
+) -> Vec<(std::ops::Range<u32>, bool)> {
+    let mut runs: Vec<(std::ops::Range<u32>, bool)> = vec![];
+    for (t, triangle) in mesh.indices.chunks_exact(3).enumerate() {
+        let pass = triangle.iter().all(|&i| test(&mesh.vertices[i as usize]));
+        let end = (t as u32 + 1) * 3;
+        match runs.last_mut() {
+            Some((range, p)) if *p == pass => range.end = end,
+            _ => runs.push((end - 3..end, pass)),
+        }
+    }
+    runs
+}
+
+// Scale a rect by `zoom` around `center`, as the vertex shader does with the UI
+fn zoom_rect(rect: egui::Rect, center: egui::Pos2, zoom: f32) -> egui::Rect {
+    egui::Rect::from_min_max(
+        center + (rect.min - center) * zoom,
+        center + (rect.max - center) * zoom,
+    )
+}
+
+// Convert egui clip rect to a physical pixel rect
+fn calculate_pixel_rect(
+    clip_rect: &egui::Rect,
+    pixels_per_point: [f32; 2],
+    target_size: [u32; 2],
+    rounding: ClipRounding,
+) -> Rect<u32, ScreenSpace> {
+    // Transform to physical pixels
+    let clip_min_x = pixels_per_point[0] * clip_rect.min.x;
+    let clip_min_y = pixels_per_point[1] * clip_rect.min.y;
+    let clip_max_x = pixels_per_point[0] * clip_rect.max.x;
+    let clip_max_y = pixels_per_point[1] * clip_rect.max.y;
+
+    // Make sure clip rect can fit within an `u32`. Unlike clamp, max and min take the bound for NaN
+    let clip_min_x = clip_min_x.max(0.0).min(target_size[0] as f32);
+    let clip_min_y = clip_min_y.max(0.0).min(target_size[1] as f32);
+    let clip_max_x = clip_max_x.max(clip_min_x).min(target_size[0] as f32);
+    let clip_max_y = clip_max_y.max(clip_min_y).min(target_size[1] as f32);
+
+    let round = |min: f32, max: f32| match rounding {
+        ClipRounding::Round => (min.round() as u32, max.round() as u32),
+        ClipRounding::Floor => (min.floor() as u32, max.floor() as u32),
+        ClipRounding::Expand => (min.floor() as u32, max.ceil() as u32),
+    };
+    let (clip_min_x, clip_max_x) = round(clip_min_x, clip_max_x);
+    let (clip_min_y, clip_max_y) = round(clip_min_y, clip_max_y);
+
+    let width = (clip_max_x - clip_min_x).max(1);
+    let height = (clip_max_y - clip_min_y).max(1);
+
+    // Clip scissor rectangle to target size.
+    let x = clip_min_x.min(target_size[0]);
+    let y = clip_min_y.min(target_size[1]);
+    let width = width.min(target_size[0] - x);
+    let height = height.min(target_size[1] - y);
+
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+// Copy a `src_width` wide image into a `dst_width` wide one at `pos`
+fn blit<T: Copy>(dst: &mut [T], dst_width: usize, src: &[T], src_width: usize, pos: [usize; 2]) {
+    for (row, line) in src.chunks(src_width).enumerate() {
+        let start = (pos[1] + row) * dst_width + pos[0];
+        dst[start..start + line.len()].copy_from_slice(line);
+    }
+}
+
+// Lower the pixels per point along X and Y in `implied` to what a clip rect reaching more than a few
+// pixels past the screen implies, returning whether it did. egui clips the root layers to the screen
+// in points, so this catches a ScreenDescriptor with a larger scale than the primitives were
+// tessellated at. A smaller one can't be told apart from a UI that doesn't fill the screen.
+fn imply_pixels_per_point(
+    implied: &mut [f32; 2],
+    clip_rect: &egui::Rect,
+    size_in_pixels: [u32; 2],
+) -> bool {
+    const TOLERANCE: f32 = 2.0;
+    let mut mismatched = false;
+    let max = clip_rect.max;
+    for (axis, extent) in [max.x, max.y].into_iter().enumerate() {
+        let size = size_in_pixels[axis] as f32;
+        if extent.is_finite() && extent > 0.0 && extent * implied[axis] > size + TOLERANCE {
+            implied[axis] = size / extent;
+            mismatched = true;
+        }
+    }
+    mismatched
+}
+
+// Add a 1 pixel outline just inside a physical pixel rect to a mesh in points
+fn add_outline(
+    mesh: &mut egui::epaint::Mesh,
+    rect: Rect<u32, ScreenSpace>,
+    pixels_per_point: [f32; 2],
+    color: egui::Color32,
+) {
+    let min = egui::pos2(
+        rect.min_x() as f32 / pixels_per_point[0],
+        rect.min_y() as f32 / pixels_per_point[1],
+    );
+    let max = egui::pos2(
+        rect.max_x() as f32 / pixels_per_point[0],
+        rect.max_y() as f32 / pixels_per_point[1],
+    );
+    let [tx, ty] = [1.0 / pixels_per_point[0], 1.0 / pixels_per_point[1]];
+    for side in [
+        egui::Rect::from_min_max(min, egui::pos2(max.x, min.y + ty)),
+        egui::Rect::from_min_max(egui::pos2(min.x, max.y - ty), max),
+        egui::Rect::from_min_max(min, egui::pos2(min.x + tx, max.y)),
+        egui::Rect::from_min_max(egui::pos2(max.x - tx, min.y), max),
+    ] {
+        mesh.add_colored_rect(side, color);
+    }
+}
+
+// Key of the sampler for wrap modes [u, v], None for the default sampler, which clamps to the edge
+fn sampler_key(wrap: [wgpu::AddressMode; 2]) -> Option<SamplerKey> {
+    match wrap == [wgpu::AddressMode::ClampToEdge; 2] {
+        true => None,
+        false => Some((
+            wgpu::FilterMode::Nearest,
+            wgpu::FilterMode::Linear,
+            wrap[0],
+            wrap[1],
+        )),
+    }
+}
+
+// Distinct color for each texture id
+fn texture_color(id: TextureId) -> egui::Color32 {
+    let hue = match id {
+        TextureId::Managed(n) => n as f32 * 0.618,
+        TextureId::User(n) => n as f32 * 0.618 + 0.5,
+    };
+    egui::color::Hsva::new(hue.fract(), 0.8, 1.0, 1.0).into()
+}
+
+// Okabe-Ito colors, which stay distinct with common color blindness
+const TINT_PALETTE: [egui::Color32; 7] = [
+    egui::Color32::from_rgb(0xE6, 0x9F, 0x00),
+    egui::Color32::from_rgb(0x56, 0xB4, 0xE9),
+    egui::Color32::from_rgb(0x00, 0x9E, 0x73),
+    egui::Color32::from_rgb(0xF0, 0xE4, 0x42),
+    egui::Color32::from_rgb(0x00, 0x72, 0xB2),
+    egui::Color32::from_rgb(0xD5, 0x5E, 0x00),
+    egui::Color32::from_rgb(0xCC, 0x79, 0xA7),
+];
+
+// Tint for each texture id in DebugMode::TextureTint, neighbouring ids get different colors
+fn texture_tint(id: TextureId) -> egui::Color32 {
+    let n = match id {
+        TextureId::Managed(n) => n,
+        TextureId::User(n) => n.wrapping_add(3),
+    };
+    TINT_PALETTE[(n % TINT_PALETTE.len() as u64) as usize]
+}
+
+// Multiply the color channels of a premultiplied color, keeping its alpha
+fn multiply_color(color: egui::Color32, tint: egui::Color32) -> egui::Color32 {
+    let mul = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+    egui::Color32::from_rgba_premultiplied(
+        mul(color.r(), tint.r()),
+        mul(color.g(), tint.g()),
+        mul(color.b(), tint.b()),
+        color.a(),
+    )
+}
+
+// Parse and validate WGSL so errors are reported instead of panicking inside wgpu
+fn validate_wgsl(
+    source: &str,
+    entry_points: &[(&str, naga::ShaderStage)],
+) -> Result<(), EguiError> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| EguiError::ShaderCompile(e.emit_to_string(source)))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| EguiError::ShaderCompile(e.to_string()))?;
+
+    for &(name, stage) in entry_points {
+        if !module
+            .entry_points
+            .iter()
+            .any(|e| e.name == name && e.stage == stage)
+        {
+            return Err(EguiError::ShaderCompile(format!(
+                "missing {:?} entry point `{}`",
+                stage, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Copy a 4 byte per pixel texture back to the CPU as RGBA rows, waiting for the GPU
+pub(crate) fn read_back(paint: &Painter, tex: &Texture, label: &str) -> Option<Vec<[u8; 4]>> {
+    // Rows of the copy have to be aligned
+    let row_bytes = tex.size.width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row_bytes = row_bytes.div_ceil(align) * align;
+    let buffer = paint.device.wgpu.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: (padded_row_bytes * tex.size.height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = paint.device.create_command_encoder();
+    encoder.copy_texture_to_buffer(
+        tex.wgpu.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_row_bytes),
+                rows_per_image: None,
+            },
+        },
+        tex.extent,
+    );
+    paint.device.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).ok();
+    });
+    paint.device.wgpu.poll(wgpu::Maintain::Wait);
+    receiver.recv().ok()?.ok()?;
+
+    let bgra = matches!(
+        tex.format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+    let pixels = unpad_rows(&slice.get_mapped_range(), row_bytes, padded_row_bytes, bgra);
+    buffer.unmap();
+    Some(pixels)
+}
+
+// egui's RGBA pixels in the channel order of a texture in `format`
+fn texels(pixels: &[egui::Color32], format: wgpu::TextureFormat) -> Vec<egui::Color32> {
+    let bgra = matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+    pixels
+        .iter()
+        .map(|p| match p.to_array() {
+            [r, g, b, a] if bgra => egui::Color32::from_rgba_premultiplied(b, g, r, a),
+            _ => *p,
+        })
+        .collect()
+}
+
+// Drop the padding at the end of each `padded_row_bytes` row and swap BGRA pixels to RGBA
+fn unpad_rows(data: &[u8], row_bytes: u32, padded_row_bytes: u32, bgra: bool) -> Vec<[u8; 4]> {
+    data.chunks_exact(padded_row_bytes as usize)
+        .flat_map(|row| row[..row_bytes as usize].chunks_exact(4))
+        .map(|p| match bgra {
+            true => [p[2], p[1], p[0], p[3]],
+            false => [p[0], p[1], p[2], p[3]],
+        })
+        .collect()
+}
+
+// Poll a future once, for wgpu's native futures that are ready as soon as they're made
+fn poll_ready<F: std::future::Future>(future: F) -> Option<F::Output> {
+    use std::task::{RawWaker, RawWakerVTable, Waker};
+    // A waker that does nothing, the future is never polled again
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    // SAFETY: the vtable functions ignore the data pointer, so a null one is fine
+    let waker = unsafe { Waker::from_raw(clone(std::ptr::null())) };
+    let mut cx = std::task::Context::from_waker(&waker);
+    match std::future::Future::poll(Box::pin(future).as_mut(), &mut cx) {
+        std::task::Poll::Ready(output) => Some(output),
+        std::task::Poll::Pending => None,
+    }
+}
+
+use parrot::{device::Device, pipeline::PipelineLayout, vertex::VertexLayout};
+use wgpu::{MultisampleState, ShaderModule};
+/// Name of the built in shader's vertex entry point
+pub const VS_ENTRY: &str = "vs_main";
+/// Name of the built in shader's fragment entry point
+pub const FS_ENTRY: &str = "fs_main";
+
+/// Custom pipeline function. Pass [`VS_ENTRY`] and [`FS_ENTRY`] for the built in shader's entry
+/// points, and the format of the target it draws into, e.g. [`ColorSpace::target_format`].
+#[allow(clippy::too_many_arguments)]
+pub fn egui_parrot_pipeline(
+    dev: &Device,
+    pipe_l: PipelineLayout,
+    vert_l: VertexLayout,
+    shader: ShaderModule,
+    format: wgpu::TextureFormat,
+    multi: MultisampleState,
+    name: Option<&str>,
+    vs_entry: &str,
+    fs_entry: &str,
+) -> parrot::pipeline::Pipeline {
+    let variant = PipelineVariant {
+        multisample: multi,
+        vs_entry,
+        fs_entry,
+        ..PipelineVariant::new(format, multi.count)
+    };
+    let wgpu = egui_render_pipeline(dev, &pipe_l, &vert_l, &shader, variant, name);
+
+    parrot::pipeline::Pipeline {
+        wgpu,
+        layout: pipe_l,
+        vertex_layout: vert_l,
+    }
+}
+
+// Settings that differ between the egui pipeline variants
+#[derive(Debug, Clone)]
+struct PipelineVariant<'a> {
+    format: wgpu::TextureFormat,
+    multisample: MultisampleState,
+    vs_entry: &'a str,
+    fs_entry: &'a str,
+    /// Whether the variant reads the egui vertex buffer
+    vertex_buffer: bool,
+    blend: wgpu::BlendState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    /// Whether the variant also writes the coverage mask, with fs_coverage
+    coverage: bool,
+    /// Whether triangles are rasterized conservatively
+    conservative: bool,
+}
+
+impl PipelineVariant<'static> {
+    // The normal UI pipeline for a target
+    fn new(format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        Self {
+            format,
+            multisample: MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            vs_entry: VS_ENTRY,
+            fs_entry: FS_ENTRY,
+            vertex_buffer: true,
+            blend: PREMULTIPLIED_BLENDING,
+            depth_stencil: None,
+            coverage: false,
+            conservative: false,
+        }
+    }
+}
+
+// Build an egui render pipeline variant
+fn egui_render_pipeline(
+    dev: &Device,
+    pipe_l: &PipelineLayout,
+    vert_l: &VertexLayout,
+    shader: &ShaderModule,
+    variant: PipelineVariant<'_>,
+    name: Option<&str>,
+) -> wgpu::RenderPipeline {
+    let vert_attrs = [vert_l.to_wgpu()];
+    let buffers: &[wgpu::VertexBufferLayout] = if variant.vertex_buffer {
+        &vert_attrs
+    } else {
+        &[]
+    };
+
+    let mut b_layouts = Vec::new();
+
+    for s in pipe_l.b_layouts.iter() {
+        b_layouts.push(&s.wgpu);
+    }
+
+    let layout = &dev
+        .wgpu
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: name,
+            bind_group_layouts: b_layouts.as_slice(),
+            push_constant_ranges: &[],
+        });
+
+    let mut targets = vec![Some(wgpu::ColorTargetState {
+        format: variant.format,
+        blend: Some(variant.blend),
+        write_mask: wgpu::ColorWrites::ALL,
+    })];
+    if variant.coverage {
+        targets.push(Some(wgpu::ColorTargetState {
+            format: COVERAGE_FORMAT,
+            blend: Some(COVERAGE_BLENDING),
+            write_mask: wgpu::ColorWrites::RED,
+        }));
+    }
+
+    let desc = wgpu::RenderPipelineDescriptor {
+        label: name,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: variant.vs_entry,
+            buffers,
+        },
+        layout: Some(layout),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            unclipped_depth: false,
+            conservative: variant.conservative,
+            cull_mode: None,
+            front_face: wgpu::FrontFace::default(),
+            polygon_mode: wgpu::PolygonMode::default(),
+            strip_index_format: None,
+        },
+        depth_stencil: variant.depth_stencil,
+        multisample: variant.multisample,
+        multiview: None,
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: variant.fs_entry,
+            targets: &targets,
+        }),
+    };
+
+    dev.wgpu.create_render_pipeline(&desc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_shader_has_every_entry_point() {
+        use naga::ShaderStage::{Fragment, Vertex};
+        // What `reload_shader` asks of a copy of the built in shader with every option on
+        let entries = [
+            (VS_ENTRY, Vertex),
+            ("vs_layered", Vertex),
+            ("vs_fullscreen", Vertex),
+            (FS_ENTRY, Fragment),
+            ("fs_blit", Fragment),
+            ("fs_overdraw", Fragment),
+            ("fs_coverage", Fragment),
+            ("fs_subpixel_mask", Fragment),
+            ("fs_subpixel_color", Fragment),
+        ];
+        assert!(validate_wgsl(SHADER, &entries).is_ok());
+        assert!(matches!(
+            validate_wgsl(SHADER, &[("vs_missing", Vertex)]),
+            Err(EguiError::ShaderCompile(_))
+        ));
+        assert!(validate_wgsl("fn broken(", &entries).is_err());
+    }
+
+    #[test]
+    fn scissors_scale_with_render_scale() {
+        let clip = egui::Rect::from_min_max(egui::pos2(10.0, 5.0), egui::pos2(30.0, 25.0));
+        let mut screen = ScreenDescriptor::new([100, 50], 1.5);
+        let rect = |s: &ScreenDescriptor| {
+            let size = s.render_size_in_pixels();
+            calculate_pixel_rect(&clip, s.scissor_scale(), size, ClipRounding::Round)
+        };
+        assert_eq!(
+            rect(&screen),
+            Rect::new(Point2D::new(15, 8), Size2D::new(30, 30))
+        );
+
+        screen.render_scale = 2.0;
+        assert_eq!(screen.render_size_in_pixels(), [200, 100]);
+        assert_eq!(screen.scissor_scale(), [3.0, 3.0]);
+        assert_eq!(
+            rect(&screen),
+            Rect::new(Point2D::new(30, 15), Size2D::new(60, 60))
+        );
+    }
+
+    #[test]
+    fn clip_rects_past_the_screen_imply_a_smaller_scale() {
+        let rect = |x: f32, y: f32| egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(x, y));
+        // Tessellated at 1 point per pixel but described as 2
+        let mut implied = [2.0, 2.0];
+        assert!(imply_pixels_per_point(
+            &mut implied,
+            &rect(100.0, 50.0),
+            [100, 50]
+        ));
+        assert_eq!(implied, [1.0, 1.0]);
+
+        // Matching scales, and clip rects within the tolerance, are left alone
+        let mut implied = [2.0, 2.0];
+        assert!(!imply_pixels_per_point(
+            &mut implied,
+            &rect(50.0, 25.0),
+            [100, 50]
+        ));
+        assert!(!imply_pixels_per_point(
+            &mut implied,
+            &rect(50.5, 25.5),
+            [100, 50]
+        ));
+        assert!(!imply_pixels_per_point(
+            &mut implied,
+            &rect(f32::INFINITY, 10.0),
+            [100, 50]
+        ));
+        assert_eq!(implied, [2.0, 2.0]);
+
+        // Each axis on its own
+        assert!(imply_pixels_per_point(
+            &mut implied,
+            &rect(50.0, 50.0),
+            [100, 50]
+        ));
+        assert_eq!(implied, [2.0, 1.0]);
+    }
+
+    #[test]
+    fn physical_clip_rects_are_not_scaled() {
+        let clip = egui::Rect::from_min_max(egui::pos2(10.0, 10.0), egui::pos2(40.0, 20.0));
+        let logical = ScreenDescriptor::new([100, 100], 2.0);
+        let physical = ScreenDescriptor {
+            clip_space: ClipSpace::Physical,
+            ..logical
+        };
+        let rect = |s: &ScreenDescriptor| {
+            calculate_pixel_rect(&clip, s.scissor_scale(), [100, 100], ClipRounding::Round)
+        };
+        assert_eq!(
+            rect(&logical),
+            Rect::new(Point2D::new(20, 20), Size2D::new(60, 20))
+        );
+        assert_eq!(
+            rect(&physical),
+            Rect::new(Point2D::new(10, 10), Size2D::new(30, 10))
+        );
+
+        // The window is in the same units as the clip rects
+        assert_eq!(logical.window_clip_rect().max, egui::pos2(50.0, 50.0));
+        assert_eq!(physical.window_clip_rect().max, egui::pos2(100.0, 100.0));
+    }
+
+    #[test]
+    fn default_settings_leave_colors_alone() {
+        let settings = ShaderSettings::default();
+        assert_eq!((settings.brightness, settings.gamma), (1.0, 1.0));
+
+        let uniform = Uniform::new(settings, [200.0, 100.0], ColorSpace::LinearTarget);
+        assert_eq!(uniform.screen_size_in_points, [200.0, 100.0]);
+        assert_eq!((uniform.brightness, uniform.gamma), (1.0, 1.0));
+        assert_eq!(uniform.linear_target, 1);
+        assert_eq!((uniform.zoom, uniform.feather), (1.0, 1.0));
+        assert_eq!(uniform.ui_tint, [1.0; 4]);
+        let srgb = Uniform::new(settings, [200.0, 100.0], ColorSpace::SrgbTarget);
+        assert_eq!(srgb.linear_target, 0);
+    }
+
+    #[test]
+    fn screen_validation_rejects_unrenderable_descriptors() {
+        let screen = ScreenDescriptor::new([800, 600], 1.0);
+        assert_eq!(screen.validate(4096), Ok(()));
+
+        let invalid =
+            |s: ScreenDescriptor, max| matches!(s.validate(max), Err(EguiError::InvalidScreen(_)));
+        assert!(invalid(
+            ScreenDescriptor {
+                pixels_per_point: 0.0,
+                ..screen
+            },
+            4096
+        ));
+        assert!(invalid(
+            ScreenDescriptor {
+                render_scale: 0.0,
+                ..screen
+            },
+            4096
+        ));
+        assert!(invalid(
+            ScreenDescriptor {
+                scale_factors: Some([1.0, f32::NAN]),
+                ..screen
+            },
+            4096
+        ));
+        assert!(invalid(ScreenDescriptor::new([0, 600], 1.0), 4096));
+        assert!(invalid(screen, 512));
+        // The render target is checked, not the window
+        assert!(invalid(
+            ScreenDescriptor {
+                render_scale: 8.0,
+                ..screen
+            },
+            4096
+        ));
+    }
+
+    #[test]
+    fn rotation_moves_rects_onto_the_turned_target() {
+        let size = [100.0, 50.0];
+        let rect = Rect::new(Point2D::new(10.0, 5.0), Size2D::new(20.0, 10.0));
+        let at = |x, y, w, h| Rect::new(Point2D::new(x, y), Size2D::new(w, h));
+        for (rotation, rotated, target) in [
+            (
+                ScreenRotation::None,
+                at(10.0, 5.0, 20.0, 10.0),
+                [100.0, 50.0],
+            ),
+            (
+                ScreenRotation::Deg90,
+                at(35.0, 10.0, 10.0, 20.0),
+                [50.0, 100.0],
+            ),
+            (
+                ScreenRotation::Deg180,
+                at(70.0, 35.0, 20.0, 10.0),
+                [100.0, 50.0],
+            ),
+            (
+                ScreenRotation::Deg270,
+                at(5.0, 70.0, 10.0, 20.0),
+                [50.0, 100.0],
+            ),
+        ] {
+            assert_eq!(rotation.rotate_size(size), target);
+            assert_eq!(rotation.rotate_rect(rect, size), rotated);
+            assert_eq!(rotation.inverse().rotate_rect(rotated, target), rect);
+        }
+    }
+
+    #[test]
+    fn window_clip_rects_scissor_the_whole_target() {
+        // 101x51 at 1.5 rounds to a 152x77 target, but the window's edge scales to 151.5x76.5
+        let screen = ScreenDescriptor {
+            render_scale: 1.5,
+            ..ScreenDescriptor::new([101, 51], 1.0)
+        };
+        let window = screen.screen_rect();
+        let full = Rect::from_size(Size2D::new(152, 77));
+        assert_eq!(screen.render_size_in_pixels(), [152, 77]);
+        assert_eq!(
+            screen.scissor_rect(&window, &window, ClipRounding::Floor),
+            full
+        );
+        assert_ne!(
+            calculate_pixel_rect(
+                &window,
+                screen.scissor_scale(),
+                [152, 77],
+                ClipRounding::Floor
+            ),
+            full
+        );
+
+        // Smaller clip rects are scaled as usual
+        let clip = window.shrink(10.0);
+        assert_eq!(
+            screen.scissor_rect(&clip, &clip, ClipRounding::Floor),
+            Rect::new(Point2D::new(15, 15), Size2D::new(121, 46))
+        );
+    }
+
+    #[test]
+    fn index_offsets_stop_at_u32_max() {
+        assert_eq!(offset_indices(&[0, 1, 2], 10), Ok(vec![10, 11, 12]));
+        let base = u32::MAX as usize - 2;
+        assert_eq!(
+            offset_indices(&[0, 2], base),
+            Ok(vec![u32::MAX - 2, u32::MAX])
+        );
+        assert_eq!(
+            offset_indices(&[0, 3], base),
+            Err(EguiError::TooManyVertices {
+                count: u32::MAX as usize + 2
+            })
+        );
+    }
+
+    #[test]
+    fn font_atlas_is_the_default_texture() {
+        assert_eq!(FONT_ATLAS_ID, TextureId::default());
+    }
+
+    #[test]
+    fn pixel_rects_of_broken_clip_rects_stay_in_the_target() {
+        let pixel_rect = |min: egui::Pos2, max: egui::Pos2| {
+            let clip = egui::Rect::from_min_max(min, max);
+            calculate_pixel_rect(&clip, [1.0, 1.0], [100, 50], ClipRounding::Round)
+        };
+        let at = |x, y, w, h| Rect::new(Point2D::new(x, y), Size2D::new(w, h));
+        let nan = egui::pos2(f32::NAN, f32::NAN);
+        assert_eq!(pixel_rect(nan, egui::pos2(20.0, 10.0)), at(0, 0, 20, 10));
+        let infinite = egui::pos2(f32::INFINITY, f32::INFINITY);
+        assert_eq!(
+            pixel_rect(egui::pos2(10.0, 5.0), infinite),
+            at(10, 5, 90, 45)
+        );
+        let inverted = pixel_rect(egui::pos2(30.0, 20.0), egui::pos2(10.0, 5.0));
+        assert_eq!(inverted, at(30, 20, 1, 1));
+        let outside = pixel_rect(egui::pos2(200.0, 200.0), egui::pos2(300.0, 300.0));
+        assert!(outside.is_empty());
+    }
+
+    // A device on any adapter, for tests needing wgpu objects but no surface
+    pub(crate) fn test_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+        test_device_with(wgpu::Limits::default())
+    }
+
+    // test_device with lower limits than the default ones
+    pub(crate) fn test_device_with(limits: wgpu::Limits) -> Option<(wgpu::Device, wgpu::Queue)> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))?;
+        let descriptor = wgpu::DeviceDescriptor {
+            label: Some("Egui test device"),
+            features: wgpu::Features::empty(),
+            limits,
+        };
+        pollster::block_on(adapter.request_device(&descriptor, None)).ok()
+    }
+
+    // parrot only makes a Painter for a window surface, which headless tests don't have. These
+    // mirror the fields of parrot 0.4's Device and Painter, in order, to build one around
+    // test_device instead; transmute checks the sizes match and the accessors check the values.
+    struct DeviceFields {
+        _wgpu: wgpu::Device,
+        _queue: wgpu::Queue,
+        _surface: Option<wgpu::Surface>,
+        _size: Size2D<u32, ScreenSpace>,
+    }
+    struct PainterFields {
+        _device: DeviceFields,
+        _sample_count: u32,
+        _pref_format: wgpu::TextureFormat,
+    }
+
+    // A Painter without a surface, for tests rendering into textures. `None` without an adapter.
+    pub(crate) fn test_painter() -> Option<Painter> {
+        test_painter_with(wgpu::Limits::default())
+    }
+
+    // test_painter on a device with `limits`
+    pub(crate) fn test_painter_with(limits: wgpu::Limits) -> Option<Painter> {
+        let (device, queue) = test_device_with(limits)?;
+        let format = ColorSpace::SrgbTarget.target_format();
+        let fields = PainterFields {
+            _device: DeviceFields {
+                _wgpu: device,
+                _queue: queue,
+                _surface: None,
+                _size: Size2D::new(1, 1),
+            },
+            _sample_count: 1,
+            _pref_format: format,
+        };
+        // SAFETY: PainterFields has the same fields as Painter, which has no other invariants
+        let paint: Painter = unsafe { std::mem::transmute(fields) };
+        assert_eq!(paint.sample_count(), 1);
+        assert_eq!(paint.preferred_format(), format);
+        assert_eq!(paint.size(), Size2D::new(1, 1));
+        assert!(paint.device.surface.is_none());
+        Some(paint)
+    }
+
+    // The font atlas as a single white texel, which is all the test quads sample
+    pub(crate) fn white_atlas() -> egui::TexturesDelta {
+        let mut textures = egui::TexturesDelta::default();
+        textures.set.insert(
+            FONT_ATLAS_ID,
+            egui::epaint::ImageDelta::full(egui::ColorImage::new([1, 1], egui::Color32::WHITE)),
+        );
+        textures
+    }
+
+    // A quad of `color` over `rect`, clipped to `clip`
+    pub(crate) fn quad(
+        rect: egui::Rect,
+        color: egui::Color32,
+        clip: egui::Rect,
+    ) -> egui::ClippedPrimitive {
+        let mut mesh = egui::epaint::Mesh::default();
+        mesh.add_colored_rect(rect, color);
+        egui::ClippedPrimitive {
+            clip_rect: clip,
+            primitive: egui::epaint::Primitive::Mesh(mesh),
+        }
+    }
+
+    // An sRGB target of `size` to render into and read back
+    pub(crate) fn test_target(paint: &Painter, size: [u32; 2]) -> Texture {
+        paint.texture(
+            Size2D::new(size[0], size[1]),
+            ColorSpace::SrgbTarget.target_format(),
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            Some("Egui test target"),
+            false,
+        )
+    }
+
+    // The rect of a whole target of `size` in points, at 1 pixel per point
+    pub(crate) fn whole(size: [u32; 2]) -> egui::Rect {
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(size[0] as f32, size[1] as f32))
+    }
+
+    // Read back `target`, which has to succeed on a device the tests render with
+    pub(crate) fn pixels(paint: &Painter, target: &Texture) -> Vec<[u8; 4]> {
+        read_back(paint, target, "Egui test readback buffer").expect("readback failed")
+    }
+
+    #[test]
+    fn layouts_missing_bind_groups_are_refused() {
+        let device = match test_device() {
+            Some((device, _)) => device,
+            None => return,
+        };
+        let set = |index: usize, size: usize| {
+            let wgpu = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[],
+            });
+            parrot::binding::BindingGroupLayout::new(index as u32, wgpu, size)
+        };
+        let layout = |b_layouts| PipelineLayout { b_layouts };
 
-                    // Get clipping rect
-                    let pixel_rect = calculate_pixel_rect(
-                        &primative.clip_rect,
-                        pixels_per_point,
-                        size_in_pixels,
-                    );
-                    indices.append(&mut mesh.indices.iter().map(|i| i + si2 as u32).collect());
-                    vertices.append(&mut mesh.vertices);
-                    groups.push(Group {
-                        range: si..indices.len() as u32,
-                        tex_id: mesh.texture_id,
-                        pixel_rect,
-                    });
-                }
-            }
+        assert_eq!(
+            check_layout(&layout(vec![set(UNIFORM_SET, 1), set(TEXTURE_SET, 2)])),
+            Ok(())
+        );
+        // Truncated before the texture set
+        assert_eq!(
+            check_layout(&layout(vec![set(UNIFORM_SET, 1)])),
+            Err(EguiError::MissingBindGroupLayout {
+                index: TEXTURE_SET,
+                purpose: "texture"
+            })
+        );
+        assert_eq!(
+            check_layout(&layout(vec![])),
+            Err(EguiError::MissingBindGroupLayout {
+                index: UNIFORM_SET,
+                purpose: "uniform"
+            })
+        );
+        // A texture set without the sampler
+        assert!(check_layout(&layout(vec![set(UNIFORM_SET, 1), set(TEXTURE_SET, 1)])).is_err());
+    }
+
+    #[test]
+    fn geometry_splits_into_chunks_under_the_limit() {
+        let vertex = |x| egui::epaint::Vertex {
+            pos: egui::pos2(x, 0.0),
+            uv: egui::epaint::WHITE_UV,
+            color: egui::Color32::WHITE,
+        };
+        let group = |range| Group {
+            range,
+            tex_id: FONT_ATLAS_ID,
+            pixel_rect: Rect::from_size(Size2D::new(10, 10)),
+            bounds: egui::Rect::NOTHING,
+            opaque: false,
+            text: false,
+            chunk: 0,
+            primitive: None,
+        };
+        // Three groups of a triangle each, and one of nine vertices too large for any buffer
+        let mut vertices: Vec<_> = (0..18).map(|x| vertex(x as f32)).collect();
+        let mut indices: Vec<u32> = (0..18).collect();
+        let mut groups = vec![group(0..3), group(3..6), group(6..9), group(9..18)];
+        // Room for two triangles per chunk
+        let max = 6 * std::mem::size_of::<Vertex>() as u64;
+        let mut errors = vec![];
+        let chunks = split_chunks(&mut vertices, &mut indices, &mut groups, max, &mut errors);
+
+        assert_eq!(
+            (vertices.len(), indices.clone()),
+            (6, vec![0, 1, 2, 3, 4, 5])
+        );
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].1, [0, 1, 2]);
+        assert_eq!(chunks[0].0[0].pos.x, 6.0);
+        let placed: Vec<_> = groups.iter().map(|g| (g.chunk, g.range.clone())).collect();
+        assert_eq!(placed[..3], [(0, 0..3), (0, 3..6), (1, 0..3)]);
+        assert_eq!(groups[3].range, 0..0);
+        assert!(matches!(errors[..], [EguiError::BufferResize { .. }]));
+    }
+
+    #[test]
+    fn selftest_passes() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let report = selftest(&mut paint);
+        assert!(!report.checks.is_empty());
+        let failures: Vec<_> = report.failures().collect();
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+
+    #[test]
+    fn readback_rows_drop_padding() {
+        // A 2x2 BGRA texture, filled blue on top and red below, in 256 byte rows
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let mut data = vec![0xAA; 2 * align as usize];
+        for (row, bgra) in [[255, 0, 0, 255], [0, 0, 255, 128]].into_iter().enumerate() {
+            let start = row * align as usize;
+            data[start..start + 8].copy_from_slice(&[bgra, bgra].concat());
         }
+        assert_eq!(
+            unpad_rows(&data, 8, align, true),
+            [
+                [0, 0, 255, 255],
+                [0, 0, 255, 255],
+                [255, 0, 0, 128],
+                [255, 0, 0, 128]
+            ]
+        );
+        assert_eq!(unpad_rows(&data, 8, align, false)[2], [0, 0, 255, 128]);
+    }
 
-        // Update buffers
-        if let Some(v) = paint.update_vertex_buffer(vertices.as_slice(), &mut self.vertex_buffer) {
-            self.vertex_buffer = v;
+    #[test]
+    fn recorded_bundles_replay_the_ui() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [8, 4];
+        let left = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(4.0, 4.0));
+        let primitives = vec![quad(left, egui::Color32::RED, whole(size))];
+        let screen = ScreenDescriptor::new(size, 1.0);
+        paint.update_pipeline(&mut pipe, (white_atlas(), primitives, screen));
+        let bundle = pipe.record_bundle(&paint);
+
+        // Replayed twice, into two targets
+        let targets = [test_target(&paint, size), test_target(&paint, size)];
+        let mut encoder = paint.device.create_command_encoder();
+        for target in &targets {
+            let mut pass = wgpu::RenderPass::begin(
+                &mut encoder,
+                &target.view,
+                None,
+                None,
+                PassOp::Clear(Rgba::TRANSPARENT),
+            );
+            pipe.execute_bundle(&mut pass, &bundle);
         }
-        if let Some(i) = paint.update_index_buffer_32(indices, &mut self.index_buffer) {
-            self.index_buffer = i;
+        paint.device.submit(vec![encoder.finish()]);
+        for target in &targets {
+            let pixels = pixels(&paint, target);
+            assert_eq!(pixels[8 + 1], [255, 0, 0, 255]);
+            assert_eq!(pixels[8 + 6], [0, 0, 0, 0]);
         }
-        self.groups = groups;
+        assert!(pipe.take_errors().is_empty());
+    }
 
-        for set in context.0.set {
-            // Function to get color from image delta
-            let set_data = |d: ImageDelta| -> Vec<egui::Color32> {
-                match d {
-                    ImageDelta {
-                        image: ImageData::Color(c),
-                        ..
-                    } => {
-                        let data = c.pixels;
-                        data
-                    }
-                    ImageDelta {
-                        image: ImageData::Font(f),
-                        ..
-                    } => {
-                        let data = f.srgba_pixels(1f32).collect::<Vec<egui::Color32>>();
-                        data
+    #[test]
+    fn render_binds_the_uniform_over_other_bindings() {
+        use pigeon_2d::pipeline::Render;
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [8, 4];
+        let left = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(4.0, 4.0));
+        let primitives = vec![quad(left, egui::Color32::RED, whole(size))];
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0)),
+        );
+
+        // What a scene drawn before the UI could leave bound, in the uniform's set
+        let layout = paint
+            .device
+            .wgpu
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[],
+            });
+        let unrelated = paint
+            .device
+            .wgpu
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[],
+            });
+        let target = test_target(&paint, size);
+        let mut encoder = paint.device.create_command_encoder();
+        {
+            let mut pass = wgpu::RenderPass::begin(
+                &mut encoder,
+                &target.view,
+                None,
+                None,
+                PassOp::Clear(Rgba::TRANSPARENT),
+            );
+            pass.set_bind_group(UNIFORM_SET as u32, &unrelated, &[]);
+            pipe.render(&mut paint, &mut pass);
+        }
+        paint.device.submit(vec![encoder.finish()]);
+        let pixels = pixels(&paint, &target);
+        assert_eq!(pixels[8 + 1], [255, 0, 0, 255]);
+        assert_eq!(pixels[8 + 6], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn a_lone_quad_lands_where_the_screen_size_puts_it() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        // 8x4 points at 2 pixels per point, the quad covering points 2..4 and 1..3
+        let size = [16, 8];
+        let rect = egui::Rect::from_min_max(egui::pos2(2.0, 1.0), egui::pos2(4.0, 3.0));
+        let clip = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(8.0, 4.0));
+        let primitives = vec![quad(rect, egui::Color32::GREEN, clip)];
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 2.0)),
+        );
+        let target = test_target(&paint, size);
+        pipe.render_layer(&mut paint, &target);
+
+        let pixels = pixels(&paint, &target);
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let covered = (4..8).contains(&x) && (2..6).contains(&y);
+                let expected = if covered { [0, 255, 0, 255] } else { [0; 4] };
+                assert_eq!(pixels[(y * size[0] + x) as usize], expected, "at {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn vertex_buffers_grow_or_update_in_place() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [16, 4];
+        let stride = std::mem::size_of::<Vertex>();
+        let target = test_target(&paint, size);
+        // A red quad in each of the 4 pixel wide columns in `columns`
+        let mut frame = |pipe: &mut EguiPipe, columns: &[u32]| {
+            let primitives = columns
+                .iter()
+                .map(|&c| {
+                    let min = egui::pos2(c as f32 * 4.0, 0.0);
+                    let rect = egui::Rect::from_min_size(min, egui::vec2(4.0, 4.0));
+                    quad(rect, egui::Color32::RED, whole(size))
+                })
+                .collect();
+            let screen = ScreenDescriptor::new(size, 1.0);
+            paint.update_pipeline(pipe, (white_atlas(), primitives, screen));
+            pipe.render_layer(&mut paint, &target);
+            let pixels = pixels(&paint, &target);
+            for c in 0..4 {
+                let expected = if columns.contains(&c) {
+                    [255, 0, 0, 255]
+                } else {
+                    [0; 4]
+                };
+                assert_eq!(pixels[(c * 4 + 1) as usize], expected, "column {c}");
+            }
+        };
+
+        frame(&mut pipe, &[0]);
+        let (small, _) = pipe.buffer_capacities();
+        assert!(small >= 4);
+        // Too many vertices for the buffer, so it's replaced by a larger one
+        frame(&mut pipe, &[0, 1, 2]);
+        let (grown, _) = pipe.buffer_capacities();
+        assert!(grown >= 12 && grown > small);
+        assert_eq!(pipe.last_vertex_upload_bytes(), 12 * stride);
+        // The same number of vertices moved is written into the same buffer
+        frame(&mut pipe, &[1, 2, 3]);
+        assert_eq!(pipe.buffer_capacities().0, grown);
+        assert_eq!(pipe.last_vertex_upload_bytes(), 12 * stride);
+        // and fewer don't shrink it
+        frame(&mut pipe, &[3]);
+        assert_eq!(pipe.buffer_capacities().0, grown);
+        assert!(pipe.take_errors().is_empty());
+    }
+
+    #[test]
+    fn filled_textures_read_back() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let id = TextureId::Managed(3);
+        let colors = [
+            egui::Color32::RED,
+            egui::Color32::GREEN,
+            egui::Color32::BLUE,
+            egui::Color32::from_rgba_premultiplied(10, 20, 30, 40),
+        ];
+        let mut textures = white_atlas();
+        let image = egui::ColorImage {
+            size: [2, 2],
+            pixels: colors.to_vec(),
+        };
+        textures.set.insert(id, ImageDelta::full(image));
+        paint.update_pipeline(
+            &mut pipe,
+            (textures, vec![], ScreenDescriptor::new([4, 4], 1.0)),
+        );
+
+        let (size, pixels) = pipe.read_texture(&paint, id).unwrap();
+        assert_eq!(size, Size2D::new(2, 2));
+        let expected: Vec<_> = colors
+            .iter()
+            .map(|c| {
+                let [r, g, b, a] = c.to_array();
+                Rgba8::new(r, g, b, a)
+            })
+            .collect();
+        assert_eq!(pixels, expected);
+        assert!(pipe.read_texture(&paint, TextureId::Managed(4)).is_none());
+    }
+
+    #[test]
+    fn frames_past_the_buffer_limit_are_drawn_in_chunks() {
+        // 25 vertices fit in a buffer, so 6 of the quads
+        let limits = wgpu::Limits {
+            max_buffer_size: 512,
+            ..Default::default()
+        };
+        let mut paint = match test_painter_with(limits) {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        // 16 quads 2 pixels wide, clipped apart so each is a group of its own
+        let size = [32, 1];
+        let primitives = (0..16)
+            .map(|i| {
+                let min = egui::pos2(i as f32 * 2.0, 0.0);
+                let rect = egui::Rect::from_min_size(min, egui::vec2(2.0, 1.0));
+                quad(rect, egui::Color32::RED, rect)
+            })
+            .collect();
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0)),
+        );
+        // The first chunk is in the pipe's own buffers
+        assert_eq!(pipe.chunks.len(), 2);
+        assert_eq!(pipe.buffer_capacities().0, 24);
+
+        let target = test_target(&paint, size);
+        pipe.render_layer(&mut paint, &target);
+        assert_eq!(pipe.last_render_stats().draw_calls, 16);
+        assert!(pixels(&paint, &target)
+            .iter()
+            .all(|p| *p == [255, 0, 0, 255]));
+        assert!(pipe.take_errors().is_empty());
+    }
+
+    #[test]
+    fn textures_drawn_the_longest_ago_are_evicted() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        pipe.set_max_textures(Some(3));
+        let size = [4, 4];
+        let target = test_target(&paint, size);
+        let [a, b, c, d] = [1, 2, 3, 4].map(TextureId::Managed);
+        // Upload `new` and prepare a frame drawing `drawn`, rendering it if `render`
+        let mut frame = |pipe: &mut EguiPipe, new: &[TextureId], drawn: &[TextureId], render| {
+            let mut textures = egui::TexturesDelta::default();
+            for id in new {
+                let image = egui::ColorImage::new([1, 1], egui::Color32::WHITE);
+                textures.set.insert(*id, ImageDelta::full(image));
+            }
+            let primitives = drawn
+                .iter()
+                .map(|id| {
+                    let mut mesh = egui::epaint::Mesh::with_texture(*id);
+                    let uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+                    mesh.add_rect_with_uv(whole(size), uv, egui::Color32::WHITE);
+                    egui::ClippedPrimitive {
+                        clip_rect: whole(size),
+                        primitive: egui::epaint::Primitive::Mesh(mesh),
                     }
-                }
-            };
+                })
+                .collect();
+            let screen = ScreenDescriptor::new(size, 1.0);
+            paint.update_pipeline(pipe, (textures, primitives, screen));
+            if render {
+                pipe.render_layer(&mut paint, &target);
+            }
+        };
+        let kept = |pipe: &EguiPipe| {
+            let mut ids: Vec<_> = pipe.egui_texture.keys().copied().collect();
+            ids.sort_by_key(|id| format!("{:?}", id));
+            ids
+        };
 
-            if let Some(t) = self.egui_texture.get_mut(&set.0) {
-                if let Some(pos) = set.1.pos {
-                    // Fill part of the texture
-                    let (width, height) = (set.1.image.width() as u32, set.1.image.height() as u32);
-                    let b = set_data(set.1);
-                    let data = Rgba8::align(b.as_slice());
-                    Texture::transfer(
-                        &t.0,
-                        data,
-                        Rect::new(
-                            Point2D::new(pos[0] as u32, pos[1] as u32),
-                            Size2D::new(width, height),
-                        ),
-                        &paint.device,
-                    );
+        let font = [FONT_ATLAS_ID];
+        frame(&mut pipe, &[FONT_ATLAS_ID, a, b], &[a], true);
+        frame(&mut pipe, &[], &[b], true);
+        // Prepared but never rendered, e.g. while minimized, so `a` still wasn't drawn since
+        frame(&mut pipe, &[], &[a], false);
+        frame(&mut pipe, &[c], &font, true);
+        assert_eq!(kept(&pipe), [FONT_ATLAS_ID, b, c]);
+
+        // Pinned textures stay however long ago they were drawn
+        pipe.pin_texture(b);
+        frame(&mut pipe, &[], &[c], true);
+        frame(&mut pipe, &[d], &font, true);
+        assert_eq!(kept(&pipe), [FONT_ATLAS_ID, b, d]);
+    }
+
+    #[test]
+    fn native_textures_register_under_free_ids_only() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [4, 4];
+        let texture = paint.texture(
+            Size2D::new(1, 1),
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            None,
+            false,
+        );
+        Texture::fill(&texture, &[Rgba8::new(0, 0, 255, 255)], &paint.device);
+
+        let id = TextureId::User(42);
+        assert!(!pipe.texture_id_taken(id));
+        assert_eq!(
+            pipe.register_native_texture_with_id(&paint, id, &texture),
+            Ok(())
+        );
+        assert!(pipe.texture_id_taken(id));
+        // Neither a registered id nor the font atlas' can be taken over
+        assert_eq!(
+            pipe.register_native_texture_with_id(&paint, id, &texture),
+            Err(EguiError::TextureIdTaken(id))
+        );
+        assert_eq!(
+            pipe.register_native_texture_with_id(&paint, FONT_ATLAS_ID, &texture),
+            Err(EguiError::TextureIdTaken(FONT_ATLAS_ID))
+        );
+        // Ids picked by the pipe skip the chosen one
+        let picked = pipe.register_native_texture(&paint, &texture);
+        assert_ne!(picked, id);
+
+        // Meshes with the chosen id draw the texture
+        let mut mesh = egui::epaint::Mesh::with_texture(id);
+        let uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+        mesh.add_rect_with_uv(whole(size), uv, egui::Color32::WHITE);
+        let primitives = vec![egui::ClippedPrimitive {
+            clip_rect: whole(size),
+            primitive: egui::epaint::Primitive::Mesh(mesh),
+        }];
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0)),
+        );
+        let target = test_target(&paint, size);
+        pipe.render_layer(&mut paint, &target);
+        assert_eq!(pixels(&paint, &target)[5], [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn reloading_the_shader_keeps_the_buffers() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [8, 4];
+        let left = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(4.0, 4.0));
+        let primitives = vec![quad(left, egui::Color32::RED, whole(size))];
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0)),
+        );
+        let capacities = pipe.buffer_capacities();
+
+        assert_eq!(pipe.reload_shader(&paint, SHADER), Ok(()));
+        assert_eq!(pipe.buffer_capacities(), capacities);
+        assert!(pipe.take_errors().is_empty());
+        // The prepared frame draws with the new pipeline without preparing it again
+        let target = test_target(&paint, size);
+        pipe.render_layer(&mut paint, &target);
+        let pixels = pixels(&paint, &target);
+        assert_eq!(pixels[8 + 1], [255, 0, 0, 255]);
+        assert_eq!(pixels[8 + 6], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn static_screens_upload_the_uniform_once() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = EguiPipeBuilder::new().static_screen(true).build(&paint);
+        let mut dynamic = setup(&paint);
+        // Number of uniform updates prepare hands back for a frame with a screen of `size`
+        let mut uploads = |pipe: &mut EguiPipe, size: [u32; 2]| {
+            let screen = ScreenDescriptor::new(size, 1.0);
+            pipe.prepare((white_atlas(), vec![], screen), &mut paint)
+                .len()
+        };
+
+        assert_eq!(uploads(&mut pipe, [8, 4]), 1);
+        assert_eq!(uploads(&mut pipe, [8, 4]), 0);
+        // A new size is only picked up once invalidated
+        assert_eq!(uploads(&mut pipe, [16, 8]), 0);
+        pipe.invalidate_uniform();
+        assert_eq!(uploads(&mut pipe, [16, 8]), 1);
+        assert_eq!(pipe.uniform.screen_size_in_points, [16.0, 8.0]);
+        assert_eq!(uploads(&mut pipe, [16, 8]), 0);
+
+        assert_eq!(uploads(&mut dynamic, [8, 4]), 1);
+        assert_eq!(uploads(&mut dynamic, [8, 4]), 1);
+    }
+
+    #[test]
+    fn textures_are_created_with_the_extra_usages() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let id = TextureId::Managed(1);
+        // Whether clearing texture `id` of `pipe` is valid, which it is only as a render attachment
+        let clear_is_valid = |paint: &mut Painter, pipe: &mut EguiPipe| {
+            let mut textures = white_atlas();
+            let image = egui::ColorImage::new([2, 2], egui::Color32::WHITE);
+            textures.set.insert(id, ImageDelta::full(image));
+            let screen = ScreenDescriptor::new([2, 2], 1.0);
+            paint.update_pipeline(pipe, (textures, vec![], screen));
+
+            paint
+                .device
+                .wgpu
+                .push_error_scope(wgpu::ErrorFilter::Validation);
+            let mut encoder = paint.device.create_command_encoder();
+            wgpu::RenderPass::begin(
+                &mut encoder,
+                &pipe.egui_texture[&id].0.view,
+                None,
+                None,
+                PassOp::Clear(Rgba::RED),
+            );
+            paint.device.submit(vec![encoder.finish()]);
+            pollster::block_on(paint.device.wgpu.pop_error_scope()).is_none()
+        };
+
+        let mut pipe = EguiPipeBuilder::new()
+            .extra_texture_usages(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            .build(&paint);
+        assert!(clear_is_valid(&mut paint, &mut pipe));
+        let (_, pixels) = pipe.read_texture(&paint, id).unwrap();
+        assert!(pixels.iter().all(|p| *p == Rgba8::new(255, 0, 0, 255)));
+
+        let mut pipe = setup(&paint);
+        assert!(!clear_is_valid(&mut paint, &mut pipe));
+    }
+
+    #[test]
+    fn stencil_masks_limit_the_ui_to_their_shape() {
+        use pigeon_2d::pipeline::Render;
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let device = &paint.device.wgpu;
+        let format = wgpu::TextureFormat::Depth24PlusStencil8;
+        let size = [8, 4];
+        let stencil = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Egui test stencil"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        let stencil = stencil.create_view(&Default::default());
+
+        // Write 1 into the left half of the stencil with a fullscreen triangle scissored to it
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(
+                "@vertex fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+                    let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+                    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+                }"
+                .into(),
+            ),
+        });
+        let replace = wgpu::StencilFaceState {
+            compare: wgpu::CompareFunction::Always,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op: wgpu::StencilOperation::Replace,
+        };
+        let writer = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState {
+                    front: replace,
+                    back: replace,
+                    read_mask: !0,
+                    write_mask: !0,
+                },
+                bias: Default::default(),
+            }),
+            multisample: Default::default(),
+            fragment: None,
+            multiview: None,
+        });
+        let stencil_attachment = |stencil_load| wgpu::RenderPassDepthStencilAttachment {
+            view: &stencil,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: Some(wgpu::Operations {
+                load: stencil_load,
+                store: true,
+            }),
+        };
+        let mut encoder = paint.device.create_command_encoder();
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[],
+                depth_stencil_attachment: Some(stencil_attachment(wgpu::LoadOp::Clear(0))),
+            });
+            pass.set_pipeline(&writer);
+            pass.set_stencil_reference(1);
+            pass.set_scissor_rect(0, 0, size[0] / 2, size[1]);
+            pass.draw(0..3, 0..1);
+        }
+        paint.device.submit(vec![encoder.finish()]);
+
+        // A red quad over the whole target, drawn where the stencil is 1
+        let mut pipe = setup(&paint);
+        pipe.set_stencil_mask(Some(StencilMask {
+            format,
+            compare: wgpu::CompareFunction::Equal,
+            reference: 1,
+        }));
+        let primitives = vec![quad(whole(size), egui::Color32::RED, whole(size))];
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0)),
+        );
+        let target = test_target(&paint, size);
+        let mut encoder = paint.device.create_command_encoder();
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(stencil_attachment(wgpu::LoadOp::Load)),
+            });
+            pipe.render(&mut paint, &mut pass);
+        }
+        paint.device.submit(vec![encoder.finish()]);
+
+        let pixels = pixels(&paint, &target);
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let expected = if x < size[0] / 2 {
+                    [255, 0, 0, 255]
                 } else {
-                    // Fill whole texture
-                    let b = set_data(set.1);
-                    let data = Rgba8::align(b.as_slice());
-                    Texture::fill(&t.0, data, &paint.device);
-                }
-            } else {
-                // Create new texture
-                let size: Size2D<u32, ScreenSpace> =
-                    Size2D::new(set.1.image.width() as u32, set.1.image.height() as u32);
-                let b = set_data(set.1);
-                let data = Rgba8::align(b.as_slice());
-                let tex = paint.texture(
-                    size,
-                    wgpu::TextureFormat::Bgra8UnormSrgb,
-                    wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                    Some(format!("Egui texture {:?}", set.0).as_str()),
-                    false,
-                );
-                let binding = paint.binding_group(
-                    &self.core.pipeline.layout.b_layouts[1],
-                    &[&tex, &self.sampler],
-                    Some(format!("Egui texture {:?} binding group", set.0).as_str()),
-                );
-                Texture::fill(&tex, data, &paint.device);
-                self.egui_texture.insert(set.0, (tex, binding));
+                    [0; 4]
+                };
+                assert_eq!(pixels[(y * size[0] + x) as usize], expected, "at {x}, {y}");
             }
         }
+    }
 
-        // Create and update uniform
-        let uniform = Uniform {
-            screen_size_in_points: context.2.screen_size_in_points(),
-            _padding: Default::default(),
+    #[test]
+    fn buffer_memory_matches_the_capacities() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        pipe.set_shrink_policy(ShrinkPolicy::WhenUnderused {
+            fraction: 0.5,
+            frames: 2,
+        });
+        let size = [32, 1];
+        let frame = |count: usize| {
+            let primitives = (0..count)
+                .map(|i| {
+                    let min = egui::pos2(i as f32 * 2.0, 0.0);
+                    let rect = egui::Rect::from_min_size(min, egui::vec2(2.0, 1.0));
+                    quad(rect, egui::Color32::RED, rect)
+                })
+                .collect();
+            (white_atlas(), primitives, ScreenDescriptor::new(size, 1.0))
         };
-        vec![(&mut self.core.uniforms[0], vec![uniform])]
+        let expected = |pipe: &EguiPipe| {
+            let (vertices, indices) = pipe.buffer_capacities();
+            let index_size = match pipe.index_buffer {
+                EguiIndexBuffer::U16(_) => std::mem::size_of::<u16>(),
+                EguiIndexBuffer::U32(_) => std::mem::size_of::<u32>(),
+            };
+            (
+                vertices * std::mem::size_of::<Vertex>(),
+                indices * index_size,
+            )
+        };
+
+        paint.update_pipeline(&mut pipe, frame(16));
+        let large = pipe.buffer_capacities();
+        assert!(large.0 >= 16 * 4 && large.1 >= 16 * 6);
+        assert_eq!(pipe.buffer_memory_bytes(), expected(&pipe));
+
+        // Two underused frames in a row reallocate the buffers to fit
+        paint.update_pipeline(&mut pipe, frame(1));
+        assert_eq!(pipe.buffer_capacities(), large);
+        paint.update_pipeline(&mut pipe, frame(1));
+        let small = pipe.buffer_capacities();
+        assert!(small.0 < large.0 && small.1 < large.1);
+        assert_eq!(pipe.buffer_memory_bytes(), expected(&pipe));
     }
-}
 
-impl pigeon_2d::pipeline::Render for EguiPipe {
-    type Vertex = Vertex;
+    #[test]
+    fn repeating_textures_get_a_repeating_sampler() {
+        let repeat = wgpu::AddressMode::Repeat;
+        let clamp = wgpu::AddressMode::ClampToEdge;
+        let key = sampler_key([repeat; 2]).unwrap();
+        assert_eq!((key.2, key.3), (repeat, repeat));
+        let key = sampler_key([repeat, clamp]).unwrap();
+        assert_eq!((key.2, key.3), (repeat, clamp));
+        assert!(sampler_key([clamp; 2]).is_none());
+    }
 
-    fn render<'a>(&'a mut self, _paint: &mut parrot::Painter, pass: &mut wgpu::RenderPass<'a>) {
-        // Set pipeline
-        pass.set_parrot_pipeline(self);
+    #[test]
+    fn textures_get_different_tints() {
+        assert_ne!(
+            texture_tint(TextureId::Managed(0)),
+            texture_tint(TextureId::User(0))
+        );
+        for n in 0..16 {
+            let (a, b) = (TextureId::Managed(n), TextureId::Managed(n + 1));
+            assert_ne!(texture_tint(a), texture_tint(b));
+            let (a, b) = (TextureId::User(n), TextureId::User(n + 1));
+            assert_ne!(texture_tint(a), texture_tint(b));
+        }
+    }
 
-        // Set buffers
-        pass.set_parrot_vertex_buffer(&self.vertex_buffer);
-        pass.set_parrot_index_buffer_32(&self.index_buffer);
-        pass.set_binding(&self.bindings[0], &[]);
+    #[test]
+    fn pinned_textures_survive_being_freed() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let logo = TextureId::User(7);
+        let mut textures = white_atlas();
+        textures.set.insert(
+            logo,
+            egui::epaint::ImageDelta::full(egui::ColorImage::new([1, 1], egui::Color32::RED)),
+        );
+        let screen = ScreenDescriptor::new([1, 1], 1.0);
+        paint.update_pipeline(&mut pipe, (textures, vec![], screen));
+        pipe.pin_texture(logo);
 
-        for group in &self.groups {
-            if !group.pixel_rect.is_empty() {
-                if let Some(binding) = self.egui_texture.get(&group.tex_id) {
-                    pass.set_binding(&binding.1, &[]);
-                } else {
-                    log::warn!("Unknown texture >> {:?}", group.tex_id);
-                }
+        let mut textures = egui::TexturesDelta::default();
+        textures.free.push(logo);
+        paint.update_pipeline(&mut pipe, (textures.clone(), vec![], screen));
+        paint.update_pipeline(&mut pipe, (Default::default(), vec![], screen));
+        assert!(pipe.egui_texture.contains_key(&logo));
+
+        pipe.unpin_texture(logo);
+        paint.update_pipeline(&mut pipe, (textures, vec![], screen));
+        paint.update_pipeline(&mut pipe, (Default::default(), vec![], screen));
+        assert!(!pipe.egui_texture.contains_key(&logo));
+    }
+
+    #[test]
+    fn composites_resolve_out_of_a_multisampled_target() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [8, 4];
+        let left = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(4.0, 4.0));
+        paint.update_pipeline(
+            &mut pipe,
+            (
+                white_atlas(),
+                vec![quad(left, egui::Color32::RED, whole(size))],
+                ScreenDescriptor::new(size, 1.0),
+            ),
+        );
+
+        // The pipe and painter are single sampled, the scene is 4x
+        let multisampled = paint.device.wgpu.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Egui test multisampled target"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 4,
+            dimension: wgpu::TextureDimension::D2,
+            format: ColorSpace::SrgbTarget.target_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        let view = multisampled.create_view(&Default::default());
+        let resolve = test_target(&paint, size);
+        let mut encoder = paint.device.create_command_encoder();
+        pipe.render_composited(&mut paint, &mut encoder, &view, Some(&resolve.view), 4);
+        paint.device.submit(vec![encoder.finish()]);
 
-                // Set scissor rect
-                pass.set_scissor_rect(
-                    group.pixel_rect.origin.x,
-                    group.pixel_rect.origin.y,
-                    group.pixel_rect.width(),
-                    group.pixel_rect.height(),
+        let pixels = pixels(&paint, &resolve);
+        for y in 0..size[1] as usize {
+            for x in 0..size[0] as usize {
+                let expected = match x < 4 {
+                    true => [255, 0, 0, 255],
+                    false => [0, 0, 0, 0],
+                };
+                assert_eq!(
+                    pixels[y * size[0] as usize + x],
+                    expected,
+                    "pixel {:?}",
+                    (x, y)
                 );
-                pass.draw_parrot_indexed(group.range.clone(), 0..1);
             }
         }
+        assert!(pipe.take_errors().is_empty());
     }
-}
 
-pub fn setup(paint: &Painter) -> EguiPipe {
-    paint.pipeline_no_depth(
-        Blending::default(),
-        wgpu::TextureFormat::Bgra8UnormSrgb,
-        Some("Egui shader"),
-    )
-}
+    #[test]
+    fn one_pixel_rects_keep_their_pixel() {
+        let pixel_rect = |min_x: f32, max_x: f32, ppp: f32, rounding| {
+            let clip = egui::Rect::from_min_max(egui::pos2(min_x, 0.0), egui::pos2(max_x, 10.0));
+            calculate_pixel_rect(&clip, [ppp, 1.0], [100, 50], rounding)
+        };
+        let column = |x, w| Rect::new(Point2D::new(x, 0), Size2D::new(w, 10));
+        for rounding in [
+            ClipRounding::Round,
+            ClipRounding::Floor,
+            ClipRounding::Expand,
+        ] {
+            assert_eq!(pixel_rect(3.0, 4.0, 1.0, rounding), column(3, 1));
+            assert_eq!(pixel_rect(1.5, 2.0, 2.0, rounding), column(3, 1));
+        }
+        // Thinner than a pixel still covers the one it starts in
+        assert_eq!(pixel_rect(3.2, 3.4, 1.0, ClipRounding::Floor), column(3, 1));
+        assert_eq!(
+            pixel_rect(3.2, 3.4, 1.0, ClipRounding::Expand),
+            column(3, 1)
+        );
+        // At the edge of the target
+        assert_eq!(
+            pixel_rect(99.0, 100.0, 1.0, ClipRounding::Round),
+            column(99, 1)
+        );
+    }
 
-// Convert egui clip rect to a physical pixel rect
-fn calculate_pixel_rect(
-    clip_rect: &egui::Rect,
-    pixels_per_point: f32,
-    target_size: [u32; 2],
-) -> Rect<u32, ScreenSpace> {
-    // Transform to physical pixels
-    let clip_min_x = pixels_per_point * clip_rect.min.x;
-    let clip_min_y = pixels_per_point * clip_rect.min.y;
-    let clip_max_x = pixels_per_point * clip_rect.max.x;
-    let clip_max_y = pixels_per_point * clip_rect.max.y;
-
-    // Make sure clip rect can fit within an `u32`.
-    let clip_min_x = clip_min_x.clamp(0.0, target_size[0] as f32);
-    let clip_min_y = clip_min_y.clamp(0.0, target_size[1] as f32);
-    let clip_max_x = clip_max_x.clamp(clip_min_x, target_size[0] as f32);
-    let clip_max_y = clip_max_y.clamp(clip_min_y, target_size[1] as f32);
-
-    let clip_min_x = clip_min_x.round() as u32;
-    let clip_min_y = clip_min_y.round() as u32;
-    let clip_max_x = clip_max_x.round() as u32;
-    let clip_max_y = clip_max_y.round() as u32;
+    #[test]
+    fn one_pixel_wide_rects_rasterize() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [8, 2];
+        let line = egui::Rect::from_min_size(egui::pos2(3.0, 0.0), egui::vec2(1.0, 2.0));
+        paint.update_pipeline(
+            &mut pipe,
+            (
+                white_atlas(),
+                vec![quad(line, egui::Color32::RED, line)],
+                ScreenDescriptor::new(size, 1.0),
+            ),
+        );
+        let target = test_target(&paint, size);
+        pipe.render_layer(&mut paint, &target);
+        let pixels = pixels(&paint, &target);
+        for (i, pixel) in pixels.iter().enumerate() {
+            let expected = match i % 8 == 3 {
+                true => [255, 0, 0, 255],
+                false => [0, 0, 0, 0],
+            };
+            assert_eq!(*pixel, expected, "pixel {}", i);
+        }
+    }
 
-    let width = (clip_max_x - clip_min_x).max(1);
-    let height = (clip_max_y - clip_min_y).max(1);
+    #[test]
+    fn read_backs_start_at_the_top_row() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        // Rows of 24 bytes, padded to 256 in the copy
+        let size = [6, 3];
+        let row = |y: f32| egui::Rect::from_min_size(egui::pos2(0.0, y), egui::vec2(6.0, 1.0));
+        let frame = || {
+            (
+                white_atlas(),
+                vec![
+                    quad(row(0.0), egui::Color32::RED, whole(size)),
+                    quad(row(1.0), egui::Color32::GREEN, whole(size)),
+                ],
+                ScreenDescriptor::new(size, 1.0),
+            )
+        };
+        let rows = |pixels: Vec<[u8; 4]>| -> Vec<[u8; 4]> {
+            pixels
+                .chunks(6)
+                .map(|row| {
+                    assert!(row.iter().all(|p| *p == row[0]));
+                    row[0]
+                })
+                .collect()
+        };
+        let (red, green, clear) = ([255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 0, 0]);
+        let target = test_target(&paint, size);
 
-    // Clip scissor rectangle to target size.
-    let x = clip_min_x.min(target_size[0]);
-    let y = clip_min_y.min(target_size[1]);
-    let width = width.min(target_size[0] - x);
-    let height = height.min(target_size[1] - y);
+        paint.update_pipeline(&mut pipe, frame());
+        pipe.render_layer(&mut paint, &target);
+        assert_eq!(rows(pixels(&paint, &target)), [red, green, clear]);
 
-    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
-}
+        // Flipped, the first row of the texture is the bottom of the UI
+        pipe.set_flip_y(true);
+        paint.update_pipeline(&mut pipe, frame());
+        pipe.render_layer(&mut paint, &target);
+        assert_eq!(rows(pixels(&paint, &target)), [clear, green, red]);
+        assert!(pipe.take_errors().is_empty());
+    }
 
-use parrot::{device::Device, pipeline::PipelineLayout, vertex::VertexLayout};
-use wgpu::{MultisampleState, ShaderModule};
-// Custom pipeline function
-pub fn egui_parrot_pipeline(
-    dev: &Device,
-    pipe_l: PipelineLayout,
-    vert_l: VertexLayout,
-    shader: ShaderModule,
-    multi: MultisampleState,
-    name: Option<&str>,
-) -> parrot::pipeline::Pipeline {
-    let vert_attrs = vert_l.to_wgpu();
+    #[test]
+    fn minimized_frames_draw_nothing_and_recover() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [4, 2];
+        let target = test_target(&paint, size);
+        let frame = |size| {
+            (
+                white_atlas(),
+                vec![quad(whole([4, 2]), egui::Color32::RED, whole([4, 2]))],
+                ScreenDescriptor::new(size, 1.0),
+            )
+        };
 
-    let mut b_layouts = Vec::new();
+        paint
+            .device
+            .wgpu
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+        for (i, minimized) in [true, false, true, false].into_iter().enumerate() {
+            let screen = if minimized { [0, 0] } else { size };
+            paint.update_pipeline(&mut pipe, frame(screen));
+            pipe.render_layer(&mut paint, &target);
+            let (draws, color) = match minimized {
+                true => (0, [0, 0, 0, 0]),
+                false => (1, [255, 0, 0, 255]),
+            };
+            assert_eq!(pipe.groups.is_empty(), minimized, "frame {}", i);
+            assert_eq!(pipe.last_render_stats().draw_calls, draws, "frame {}", i);
+            assert!(pixels(&paint, &target).iter().all(|p| *p == color));
+        }
+        let error = pollster::block_on(paint.device.wgpu.pop_error_scope());
+        assert!(error.is_none(), "{:?}", error);
+        assert!(pipe.take_errors().is_empty());
+    }
 
-    for s in pipe_l.b_layouts.iter() {
-        b_layouts.push(&s.wgpu);
+    #[test]
+    fn zoom_reaches_the_shader() {
+        // Where Locals.zoom is in egui.wgsl
+        let module = naga::front::wgsl::parse_str(SHADER).unwrap();
+        let offset = module
+            .types
+            .iter()
+            .find_map(|(_, ty)| match &ty.inner {
+                naga::TypeInner::Struct { members, .. } if ty.name.as_deref() == Some("Locals") => {
+                    members
+                        .iter()
+                        .find(|m| m.name.as_deref() == Some("zoom"))
+                        .map(|m| m.offset as usize)
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(offset, 160);
+
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        pipe.set_ui_zoom(2.0);
+        paint.update_pipeline(
+            &mut pipe,
+            (white_atlas(), vec![], ScreenDescriptor::new([4, 4], 1.0)),
+        );
+        let bytes = bytemuck::bytes_of(&pipe.uniform);
+        assert_eq!(bytes[offset..offset + 4], 2.0f32.to_ne_bytes());
     }
 
-    let layout = &dev
-        .wgpu
-        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: name,
-            bind_group_layouts: b_layouts.as_slice(),
-            push_constant_ranges: &[],
-        });
+    #[test]
+    fn recreated_pipes_render_on_a_new_device() {
+        let paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut retaining = setup(&paint);
+        retaining.set_retain_textures(true);
+        let mut forgetting = setup(&paint);
+        let size = [4, 2];
+        let primitives = || vec![quad(whole(size), egui::Color32::RED, whole(size))];
+        let screen = ScreenDescriptor::new(size, 1.0);
+        let mut paint = paint;
+        for pipe in [&mut retaining, &mut forgetting] {
+            paint.update_pipeline(pipe, (white_atlas(), primitives(), screen));
+        }
 
-    let targets = [Some(wgpu::ColorTargetState {
-        format: wgpu::TextureFormat::Bgra8UnormSrgb,
-        blend: Some(wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::One,
-                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
-                dst_factor: wgpu::BlendFactor::One,
-                operation: wgpu::BlendOperation::Add,
-            },
-        }),
-        write_mask: wgpu::ColorWrites::ALL,
-    })];
+        // The old device is no longer used. It's leaked rather than dropped, because destroying a GL
+        // device terminates the EGL display every device in the process shares.
+        std::mem::forget(paint);
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let red = [255, 0, 0, 255];
+        let target = test_target(&paint, size);
+        retaining.recreate(&paint);
+        // egui doesn't send the atlas again
+        paint.update_pipeline(&mut retaining, (Default::default(), primitives(), screen));
+        retaining.render_layer(&mut paint, &target);
+        assert!(pixels(&paint, &target).iter().all(|p| *p == red));
+        assert!(retaining.take_errors().is_empty());
 
-    let desc = wgpu::RenderPipelineDescriptor {
-        label: name,
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: "vs_main",
-            buffers: &[vert_attrs],
-        },
-        layout: Some(layout),
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            unclipped_depth: false,
-            conservative: false,
-            cull_mode: None,
-            front_face: wgpu::FrontFace::default(),
-            polygon_mode: wgpu::PolygonMode::default(),
-            strip_index_format: None,
-        },
-        depth_stencil: None,
-        multisample: multi,
-        multiview: None,
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: "fs_main",
-            targets: &targets,
-        }),
-    };
+        // Without copies the atlas has to be sent again
+        forgetting.recreate(&paint);
+        assert!(forgetting.egui_texture.is_empty());
+        paint.update_pipeline(&mut forgetting, (white_atlas(), primitives(), screen));
+        forgetting.render_layer(&mut paint, &target);
+        assert!(pixels(&paint, &target).iter().all(|p| *p == red));
+        assert!(forgetting.take_errors().is_empty());
+    }
+
+    #[test]
+    fn render_stats_add_up_over_a_frame() {
+        let mut paint = match test_painter() {
+            Some(paint) => paint,
+            None => return,
+        };
+        let mut pipe = setup(&paint);
+        let size = [4, 2];
+        // Two quads clipped apart, so each is a group of its own
+        let left = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(2.0, 2.0));
+        let right = left.translate(egui::vec2(2.0, 0.0));
+        let frame = || {
+            (
+                white_atlas(),
+                vec![
+                    quad(left, egui::Color32::RED, left),
+                    quad(right, egui::Color32::GREEN, right),
+                ],
+                ScreenDescriptor::new(size, 1.0),
+            )
+        };
+        let target = test_target(&paint, size);
+        let render = |pipe: &EguiPipe, paint: &mut Painter, groups: &[usize]| {
+            let mut encoder = paint.device.create_command_encoder();
+            {
+                let mut pass = wgpu::RenderPass::begin(
+                    &mut encoder,
+                    &target.view,
+                    None,
+                    None,
+                    PassOp::Clear(Rgba::TRANSPARENT),
+                );
+                for &group in groups {
+                    pipe.render_range(paint, &mut pass, group..group + 1);
+                }
+            }
+            paint.device.submit(vec![encoder.finish()]);
+        };
 
-    let wgpu = dev.wgpu.create_render_pipeline(&desc);
+        paint.update_pipeline(&mut pipe, frame());
+        assert_eq!(pipe.group_count(), 2);
+        render(&pipe, &mut paint, &[0, 1]);
+        let stats = pipe.last_render_stats();
+        assert_eq!((stats.draw_calls, stats.scissor_changes), (2, 2));
+        let pixels = pixels(&paint, &target);
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[3], [0, 255, 0, 255]);
 
-    parrot::pipeline::Pipeline {
-        wgpu,
-        layout: pipe_l,
-        vertex_layout: vert_l,
+        // The next frame starts counting again
+        paint.update_pipeline(&mut pipe, frame());
+        assert_eq!(pipe.last_render_stats(), RenderStats::default());
+        render(&pipe, &mut paint, &[0]);
+        assert_eq!(pipe.last_render_stats().draw_calls, 1);
+        assert!(pipe.take_errors().is_empty());
     }
 }
@@ -0,0 +1,226 @@
+//! Draws egui onto a quad in a 3D scene, e.g. a floating panel.
+//!
+//! Render the UI into a texture with [`crate::EguiPipe::render_layer`], point a [`PanelQuad`] at
+//! that texture and draw the quad in your scene's render pass with whatever model view projection
+//! matrix places the panel. The quad is the unit square from (0, 0) to (1, 1) in object space with
+//! the top of the UI at y = 1, so scale it by the texture's aspect ratio to avoid stretching.
+//! Input isn't mapped back onto the quad; unproject the cursor yourself before passing it to egui.
+//! See `examples/egui_3d.rs`.
+//!
+//! With the `multiview` feature, [`PanelQuad::new_multiview`] draws the panel into every view of a
+//! multiview pass with a transform per view, e.g. both eyes of a VR headset in one pass. See
+//! `examples/egui_stereo.rs`.
+#[cfg(feature = "multiview")]
+use crate::EguiError;
+use euclid::Transform3D;
+use parrot::{
+    binding::{Binding, BindingGroup, BindingGroupLayout, BindingType},
+    transform::{ObjectSpace, ScreenSpace},
+    Painter, RenderPassExtention, Sampler, Texture, UniformBuffer,
+};
+
+/// Most views a [`PanelQuad`] holds transforms for
+pub const MAX_VIEWS: usize = 4;
+
+/// Uniform holding the quad's transform for each view
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct PanelUniform {
+    mvp: [[[f32; 4]; 4]; MAX_VIEWS],
+}
+
+/// Draws a texture rendered by [`crate::EguiPipe::render_layer`] onto a transformed quad
+#[derive(Debug)]
+pub struct PanelQuad {
+    pipeline: wgpu::RenderPipeline,
+    texture_layout: BindingGroupLayout,
+    sampler: Sampler,
+    uniform: (UniformBuffer, BindingGroup),
+    /// What the uniform was last set to
+    transforms: PanelUniform,
+    texture: Option<BindingGroup>,
+}
+
+impl PanelQuad {
+    /// Create the quad for a render pass with a `format` target and the painter's sample count
+    pub fn new(paint: &Painter, format: wgpu::TextureFormat) -> Self {
+        Self::build(paint, format, None)
+    }
+
+    /// Create the quad for a multiview render pass with `views` layers, at most [`MAX_VIEWS`],
+    /// drawn with a transform per view set by [`PanelQuad::set_view_transforms`]. Fails unless the
+    /// device has [`wgpu::Features::MULTIVIEW`].
+    ///
+    /// # Panics
+    /// If `views` is more than [`MAX_VIEWS`]
+    #[cfg(feature = "multiview")]
+    pub fn new_multiview(
+        paint: &Painter,
+        format: wgpu::TextureFormat,
+        views: std::num::NonZeroU32,
+    ) -> Result<Self, EguiError> {
+        assert!(
+            views.get() as usize <= MAX_VIEWS,
+            "A PanelQuad draws into at most {} views",
+            MAX_VIEWS
+        );
+        if !paint
+            .device
+            .wgpu
+            .features()
+            .contains(wgpu::Features::MULTIVIEW)
+        {
+            return Err(EguiError::MissingFeatures(wgpu::Features::MULTIVIEW));
+        }
+        Ok(Self::build(paint, format, Some(views)))
+    }
+
+    fn build(
+        paint: &Painter,
+        format: wgpu::TextureFormat,
+        multiview: Option<std::num::NonZeroU32>,
+    ) -> Self {
+        let uniform_layout = paint.device.create_binding_group_layout(
+            0,
+            &[Binding {
+                binding: BindingType::UniformBuffer,
+                stage: wgpu::ShaderStages::VERTEX,
+            }],
+            Some("Egui panel uniform bind group"),
+        );
+        let texture_layout = paint.device.create_binding_group_layout(
+            1,
+            &[
+                Binding {
+                    binding: BindingType::Texture {
+                        multisampled: false,
+                    },
+                    stage: wgpu::ShaderStages::FRAGMENT,
+                },
+                Binding {
+                    binding: BindingType::Sampler,
+                    stage: wgpu::ShaderStages::FRAGMENT,
+                },
+            ],
+            Some("Egui panel texture bind group"),
+        );
+        let source = match multiview {
+            #[cfg(feature = "multiview")]
+            Some(_) => include_str!("./panel_multiview.wgsl"),
+            _ => include_str!("./panel.wgsl"),
+        };
+        let shader = paint
+            .device
+            .create_wgsl_shader(source, Some("Egui panel shader"));
+
+        let layout = paint
+            .device
+            .wgpu
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Egui panel"),
+                bind_group_layouts: &[&uniform_layout.wgpu, &texture_layout.wgpu],
+                push_constant_ranges: &[],
+            });
+        let pipeline = paint
+            .device
+            .wgpu
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Egui panel"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader.wgpu,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                // Visible from both sides
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: paint.sample_count(),
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader.wgpu,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(crate::PREMULTIPLIED_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview,
+            });
+
+        let sampler = paint.sampler(
+            wgpu::FilterMode::Linear,
+            wgpu::FilterMode::Linear,
+            Some("Egui panel sampler"),
+        );
+        let buffer = paint.uniform_buffer(&[PanelUniform::default()], Some("Egui panel uniform"));
+        let binding = paint.binding_group(
+            &uniform_layout,
+            &[&buffer],
+            Some("Egui panel uniform binding group"),
+        );
+
+        Self {
+            pipeline,
+            texture_layout,
+            sampler,
+            uniform: (buffer, binding),
+            transforms: PanelUniform::default(),
+            texture: None,
+        }
+    }
+
+    /// Draw `texture` on the quad. Call again if the texture is recreated.
+    pub fn set_texture(&mut self, paint: &Painter, texture: &Texture) {
+        self.texture = Some(paint.binding_group(
+            &self.texture_layout,
+            &[texture, &self.sampler],
+            Some("Egui panel texture binding group"),
+        ));
+    }
+
+    /// Set the matrix taking the unit square to clip space, for every view
+    pub fn set_transform(
+        &mut self,
+        paint: &Painter,
+        mvp: Transform3D<f32, ObjectSpace, ScreenSpace>,
+    ) {
+        self.transforms = PanelUniform {
+            mvp: [mvp.to_arrays(); MAX_VIEWS],
+        };
+        paint
+            .device
+            .update_buffer(&[self.transforms], &mut self.uniform.0);
+    }
+
+    /// Set the matrix taking the unit square to clip space for each view of a multiview quad, e.g.
+    /// each eye's view projection times the panel's model matrix. Views past the end of `mvps`
+    /// keep their transform.
+    #[cfg(feature = "multiview")]
+    pub fn set_view_transforms(
+        &mut self,
+        paint: &Painter,
+        mvps: &[Transform3D<f32, ObjectSpace, ScreenSpace>],
+    ) {
+        for (view, mvp) in self.transforms.mvp.iter_mut().zip(mvps) {
+            *view = mvp.to_arrays();
+        }
+        paint
+            .device
+            .update_buffer(&[self.transforms], &mut self.uniform.0);
+    }
+
+    /// Draw the quad. Does nothing until a texture is set.
+    pub fn render<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if let Some(texture) = &self.texture {
+            pass.set_pipeline(&self.pipeline);
+            pass.set_binding(&self.uniform.1, &[]);
+            pass.set_binding(texture, &[]);
+            pass.draw(0..6, 0..1);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Vertex shader
+
+struct Panel {
+    // Object space of the unit square to clip space, per view. Only the first is used here.
+    mvp: array<mat4x4<f32>, 4>,
+}
+
+@group(0) @binding(0) var<uniform> r_panel: Panel;
+
+struct VertexOutput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // Two triangles covering the unit square, with the top of the UI at y = 1
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[index];
+    var out: VertexOutput;
+    out.uv = vec2<f32>(corner.x, 1.0 - corner.y);
+    out.position = r_panel.mvp[0] * vec4<f32>(corner, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+@group(1) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(1) @binding(1) var r_tex_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, in.uv);
+}
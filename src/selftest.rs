@@ -0,0 +1,153 @@
+//! Renders a known pattern through the egui pipeline and checks the pixels that come out.
+//!
+//! Color order and gamma bugs are hard to spot by eye. [`selftest()`] draws color bars, a premultiplied
+//! translucent quad and a gradient the same way the UI is drawn, reads the result back and compares
+//! it with the values egui expects, so a swapped BGRA/RGBA order or a double sRGB conversion shows
+//! up as a failed check. Pixels the UI doesn't cover have to stay transparent, and translucent
+//! ones keep their premultiplied alpha, as a window compositor blending the UI over the desktop
+//! would need. It blocks until the GPU is done, so run it at startup or from a debug menu.
+use crate::{read_back, setup, ColorSpace, ScreenDescriptor, FONT_ATLAS_ID};
+use egui::{
+    epaint::{ImageDelta, Mesh, Primitive},
+    pos2, vec2, ClippedPrimitive, Color32, ColorImage, Rect,
+};
+use euclid::Size2D;
+use parrot::Painter;
+
+// Size of the test target
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 16;
+// Largest difference per channel allowed for rounding
+const TOLERANCE: u8 = 2;
+
+/// A single pixel compared by [`selftest()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestCheck {
+    /// What the pixel is part of
+    pub name: &'static str,
+    /// Pixel position in the test target
+    pub pos: [u32; 2],
+    /// Expected RGBA value
+    pub expected: [u8; 4],
+    /// RGBA value read back from the GPU
+    pub actual: [u8; 4],
+}
+
+impl SelfTestCheck {
+    /// Whether the pixel is within rounding of the expected value
+    pub fn passed(&self) -> bool {
+        self.expected
+            .iter()
+            .zip(self.actual)
+            .all(|(e, a)| e.abs_diff(a) <= TOLERANCE)
+    }
+}
+
+/// Result of [`selftest()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Every pixel compared. Empty if the target couldn't be read back.
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Whether the target was read back and every check passed
+    pub fn passed(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|c| c.passed())
+    }
+
+    /// The checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestCheck> {
+        self.checks.iter().filter(|c| !c.passed())
+    }
+}
+
+/// Render the test pattern into an offscreen sRGB target and compare it with the expected output.
+/// See the [module docs](self) for what it covers.
+pub fn selftest(paint: &mut Painter) -> SelfTestReport {
+    let mut pipe = setup(paint);
+
+    // The font atlas' white texel is all the pattern samples
+    let mut textures = egui::TexturesDelta::default();
+    textures.set.insert(
+        FONT_ATLAS_ID,
+        ImageDelta::full(ColorImage::new([1, 1], Color32::WHITE)),
+    );
+
+    // Opaque bars and a premultiplied half transparent white along the top
+    let bars = [
+        ("red bar", Color32::from_rgb(255, 0, 0)),
+        ("green bar", Color32::from_rgb(0, 255, 0)),
+        ("blue bar", Color32::from_rgb(0, 0, 255)),
+        ("white quad", Color32::WHITE),
+        (
+            "premultiplied half white",
+            Color32::from_rgba_premultiplied(128, 128, 128, 128),
+        ),
+    ];
+    let mut mesh = Mesh::default();
+    for (i, (_, color)) in bars.iter().enumerate() {
+        let min = pos2(i as f32 * 8.0, 0.0);
+        mesh.add_colored_rect(Rect::from_min_size(min, vec2(8.0, 8.0)), *color);
+    }
+    // Black to white along the bottom, interpolated in gamma space like egui's feathering
+    let top = HEIGHT as f32 / 2.0;
+    let idx = mesh.vertices.len() as u32;
+    mesh.colored_vertex(pos2(0.0, top), Color32::BLACK);
+    mesh.colored_vertex(pos2(WIDTH as f32, top), Color32::WHITE);
+    mesh.colored_vertex(pos2(0.0, HEIGHT as f32), Color32::BLACK);
+    mesh.colored_vertex(pos2(WIDTH as f32, HEIGHT as f32), Color32::WHITE);
+    mesh.add_triangle(idx, idx + 1, idx + 2);
+    mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+
+    let primitives = vec![ClippedPrimitive {
+        clip_rect: Rect::from_min_size(pos2(0.0, 0.0), vec2(WIDTH as f32, HEIGHT as f32)),
+        primitive: Primitive::Mesh(mesh),
+    }];
+    let screen = ScreenDescriptor::new([WIDTH, HEIGHT], 1.0);
+    paint.update_pipeline(&mut pipe, (textures, primitives, screen));
+
+    let target = paint.texture(
+        Size2D::new(WIDTH, HEIGHT),
+        ColorSpace::SrgbTarget.target_format(),
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        Some("Egui selftest target"),
+        false,
+    );
+    pipe.render_layer(paint, &target);
+
+    let pixels = match read_back(paint, &target, "Egui selftest readback buffer") {
+        Some(p) => p,
+        None => {
+            log::warn!("Failed to read back the egui selftest target");
+            return SelfTestReport { checks: vec![] };
+        }
+    };
+
+    let mut expected: Vec<(&'static str, [u32; 2], [u8; 4])> = bars
+        .iter()
+        .enumerate()
+        .map(|(i, (name, color))| (*name, [i as u32 * 8 + 4, 4], color.to_array()))
+        .collect();
+    for (name, x) in [
+        ("gradient quarter", WIDTH / 4),
+        ("gradient middle", WIDTH / 2),
+    ] {
+        let value = (255.0 * (x as f32 + 0.5) / WIDTH as f32).round() as u8;
+        expected.push((name, [x, HEIGHT * 3 / 4], [value, value, value, 255]));
+    }
+    // Right of the bars nothing is drawn over the transparent clear
+    let uncovered = bars.len() as u32 * 8;
+    expected.push(("uncovered", [(uncovered + WIDTH) / 2, 4], [0, 0, 0, 0]));
+
+    let checks = expected
+        .into_iter()
+        .map(|(name, pos, expected)| SelfTestCheck {
+            name,
+            pos,
+            expected,
+            actual: pixels[(pos[1] * WIDTH + pos[0]) as usize],
+        })
+        .collect();
+    SelfTestReport { checks }
+}
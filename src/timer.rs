@@ -0,0 +1,133 @@
+use parrot::device::Device;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[derive(Debug)]
+enum TimerState {
+    /// Ready to time the next draws
+    Idle,
+    /// The start timestamp was written
+    Began,
+    /// Both timestamps were written and resolved into the readback buffer
+    Recorded,
+    /// Waiting for the readback buffer to map. Holds whether mapping succeeded once it finishes
+    Mapping(Arc<Mutex<Option<bool>>>),
+}
+
+/// Times egui's draws with timestamp queries, reading the results back without waiting on the GPU
+pub(crate) struct GpuTimer {
+    queries: wgpu::QuerySet,
+    readback: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    state: TimerState,
+    last: Option<Duration>,
+}
+
+impl GpuTimer {
+    const SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+    /// Create the timer, or `None` if the device doesn't have [`wgpu::Features::TIMESTAMP_QUERY`]
+    pub(crate) fn new(device: &Device) -> Option<Self> {
+        if !device
+            .wgpu
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+        {
+            return None;
+        }
+
+        let queries = device.wgpu.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Egui timestamp queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let readback = device.wgpu.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Egui timestamp readback buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            queries,
+            readback,
+            period: device.queue.get_timestamp_period(),
+            state: TimerState::Idle,
+            last: None,
+        })
+    }
+
+    /// Write the start timestamp, unless the last measurement is still being read back
+    pub(crate) fn begin(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let TimerState::Idle = self.state {
+            encoder.write_timestamp(&self.queries, 0);
+            self.state = TimerState::Began;
+        }
+    }
+
+    /// Write the end timestamp and resolve both into the readback buffer
+    pub(crate) fn end(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let TimerState::Began = self.state {
+            encoder.write_timestamp(&self.queries, 1);
+            encoder.resolve_query_set(&self.queries, 0..2, &self.readback, 0);
+            self.state = TimerState::Recorded;
+        }
+    }
+
+    /// Start reading back submitted timestamps and collect them once mapped. Must be called after
+    /// the encoder passed to `end` was submitted.
+    pub(crate) fn poll(&mut self, device: &Device) {
+        if let TimerState::Recorded = self.state {
+            let done = Arc::new(Mutex::new(None));
+            let callback_done = done.clone();
+            self.readback
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    if let Ok(mut done) = callback_done.lock() {
+                        *done = Some(result.is_ok());
+                    }
+                });
+            self.state = TimerState::Mapping(done);
+        }
+
+        if let TimerState::Mapping(done) = &self.state {
+            device.wgpu.poll(wgpu::Maintain::Poll);
+            let mapped = done.lock().ok().and_then(|d| *d);
+            match mapped {
+                Some(true) => {
+                    let ticks: Vec<u64> = {
+                        let data = self.readback.slice(..).get_mapped_range();
+                        bytemuck::cast_slice(&data).to_vec()
+                    };
+                    self.readback.unmap();
+                    let nanos = ticks[1].saturating_sub(ticks[0]) as f64 * self.period as f64;
+                    self.last = Some(Duration::from_nanos(nanos as u64));
+                    self.state = TimerState::Idle;
+                }
+                Some(false) => {
+                    log::warn!("Failed to read back egui timestamps");
+                    self.state = TimerState::Idle;
+                }
+                None => (),
+            }
+        }
+    }
+
+    /// GPU time of the last measurement that has been read back
+    pub(crate) fn last(&self) -> Option<Duration> {
+        self.last
+    }
+}
+
+impl std::fmt::Debug for GpuTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuTimer")
+            .field("period", &self.period)
+            .field("state", &self.state)
+            .field("last", &self.last)
+            .finish()
+    }
+}
@@ -0,0 +1,72 @@
+//! Running a whole egui frame in a winit window with one call.
+//!
+//! [`EguiWinit`] keeps the [`egui_winit::State`] turning window events into egui input, the
+//! [`egui::Context`] and the [`EguiPipe`] together, and [`EguiWinit::run_frame`] goes from input
+//! to a rendered UI. Its fields are public for anything it doesn't cover, e.g. setting pipe options
+//! or handling the platform output differently.
+use crate::{max_texture_side, setup, EguiPipe, ScreenDescriptor};
+use parrot::Painter;
+use pigeon::pipeline::Render;
+use winit::{event::WindowEvent, window::Window};
+
+/// egui's input, context and pipe for one window, see the [module docs](self)
+pub struct EguiWinit {
+    /// Turns window events into egui input and applies egui's platform output
+    pub state: egui_winit::State,
+    /// The context the UI runs in
+    pub ctx: egui::Context,
+    /// The pipe drawing the UI
+    pub pipe: EguiPipe,
+}
+
+impl EguiWinit {
+    /// Set up egui for `window`, with a pipe created by [`setup`]
+    pub fn new(paint: &Painter, window: &Window) -> Self {
+        Self {
+            state: egui_winit::State::new(max_texture_side(paint) as usize, window),
+            ctx: egui::Context::default(),
+            pipe: setup(paint),
+        }
+    }
+
+    /// Pass a window event on to egui. Returns true if egui used it, e.g. a click on a window,
+    /// so the app shouldn't act on it too.
+    pub fn on_event(&mut self, event: &WindowEvent<'_>) -> bool {
+        self.state.on_event(&self.ctx, event)
+    }
+
+    /// Take the input gathered since the last frame, run `ui` with it, apply the platform output
+    /// to `window`, then prepare the UI and render it into `pass`. Returns whether egui wants
+    /// another frame straight away, e.g. for an animation.
+    pub fn run_frame<'a>(
+        &'a mut self,
+        window: &Window,
+        paint: &mut Painter,
+        pass: &mut wgpu::RenderPass<'a>,
+        ui: impl FnOnce(&egui::Context),
+    ) -> bool {
+        let raw_input = self.state.take_egui_input(window);
+        let full_output = self.ctx.run(raw_input, ui);
+        self.pipe.set_cursor(
+            self.ctx.input().pointer.hover_pos(),
+            full_output.platform_output.cursor_icon,
+        );
+        self.state
+            .handle_platform_output(window, &self.ctx, full_output.platform_output);
+
+        let primitives = self.ctx.tessellate(full_output.shapes);
+        let screen = ScreenDescriptor::from_window(window);
+        self.pipe
+            .prepare_from_iter(paint, full_output.textures_delta, primitives, screen);
+        self.pipe.render(paint, pass);
+        full_output.needs_repaint
+    }
+}
+
+impl std::fmt::Debug for EguiWinit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EguiWinit")
+            .field("pipe", &self.pipe)
+            .finish_non_exhaustive()
+    }
+}